@@ -0,0 +1,21 @@
+use std::path::Path;
+use trackermeta::ModInfo;
+
+fn main() {
+    let modinfo = ModInfo::get(51772).unwrap();
+
+    // the filename comes from the site, keep only its last component so it can't point
+    // anywhere outside the current directory
+    let filename = Path::new(&modinfo.filename)
+        .file_name()
+        .filter(|name| !name.is_empty())
+        .expect("the module has no usable filename");
+
+    let written = modinfo.download_to_path(filename).unwrap();
+    println!(
+        "Saved {} ({} bytes, md5 {})",
+        filename.to_string_lossy(),
+        written,
+        modinfo.md5
+    );
+}
@@ -0,0 +1,123 @@
+use chrono::prelude::{DateTime, Utc};
+
+use crate::search::Listing;
+use crate::{parse, ModArchiveClient, SearchPage, SearchPages, Transport};
+
+/// A Mod Archive member profile, get one with [`Artist::get()`] using the member IDs in
+/// [`ModInfo::artists`](crate::ModInfo::artists)
+///
+/// ```rust
+/// use trackermeta::{Artist, ModInfo};
+///
+/// let modinfo = ModInfo::get(61772).unwrap();
+/// for member in &modinfo.artists {
+///     let artist = Artist::get(member.id).unwrap();
+///     println!("{} joined {} and has {} modules", artist.name, artist.join_date_text, artist.module_ids.len());
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Artist {
+    /// The member ID of the artist
+    pub id: u32,
+    /// The name the artist goes by
+    pub name: String,
+    /// The biography on their profile, empty if they didn't write one
+    pub bio: String,
+    /// When they joined Mod Archive, the site only gives the day so this is always midnight
+    pub join_date: DateTime<Utc>,
+    /// The join date as the site shows it, for example `Sat 25th Nov 2000`
+    pub join_date_text: String,
+    /// The IDs of every module credited to the artist
+    pub module_ids: Vec<u32>,
+}
+
+impl Artist {
+    /// Scrapes the profile of the member with ID `member_id` and follows every page of their
+    /// modules, that's one request for the profile plus one for every 40 modules.
+    ///
+    /// This goes through a default [`ModArchiveClient`], use [`ModArchiveClient::get_artist()`]
+    /// if you want to bring your own [`Transport`].
+    pub fn get(member_id: u32) -> Result<Artist, crate::Error> {
+        ModArchiveClient::new().get_artist(member_id)
+    }
+
+    /// Does the same extraction as [`Artist::get()`] but on a profile page you already have,
+    /// nothing is fetched so [`Artist::module_ids`] is left empty.
+    pub fn from_html(member_id: u32, html: &str) -> Result<Artist, crate::Error> {
+        parse::artist(member_id, html)
+    }
+}
+
+impl<T: Transport> ModArchiveClient<T> {
+    /// Same as [`Artist::get()`] but through this client's transport
+    pub fn get_artist(&self, member_id: u32) -> Result<Artist, crate::Error> {
+        let mut artist = Artist::from_html(
+            member_id,
+            &self.fetch_page(&self.urls().profile(member_id))?,
+        )?;
+
+        for page in self.artist_modules_pages(member_id) {
+            artist
+                .module_ids
+                .extend(page?.results.into_iter().map(|result| result.id));
+        }
+
+        Ok(artist)
+    }
+
+    /// Returns the given page (starting from 1) of the modules credited to the artist with
+    /// member ID `member_id`
+    pub fn artist_modules_page(
+        &self,
+        member_id: u32,
+        page: u32,
+    ) -> Result<SearchPage, crate::Error> {
+        self.listing_page(&Listing::ArtistModules(member_id), page)
+    }
+
+    /// Returns an iterator that walks through every page of the artist's modules
+    pub fn artist_modules_pages(&self, member_id: u32) -> SearchPages<'_, T> {
+        SearchPages::new(self, Listing::ArtistModules(member_id), 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{artist_site, fixture_client};
+    use crate::{Artist, Error};
+    use chrono::prelude::{TimeZone, Utc};
+
+    const ARTIST: &str = include_str!("../tests/fixtures/artist.html");
+    const ARTIST_NOT_FOUND: &str = include_str!("../tests/fixtures/artist_not_found.html");
+
+    #[test]
+    fn fixture_artist() {
+        let artist = Artist::from_html(69141, ARTIST).unwrap();
+        assert_eq!(artist.id, 69141);
+        assert_eq!(artist.name, "Yrde");
+        assert_eq!(
+            artist.bio,
+            "Trance & dream music from Helsinki,\nwriting XMs since 1997."
+        );
+        assert_eq!(artist.join_date_text, "Sat 25th Nov 2000");
+        assert_eq!(
+            artist.join_date,
+            Utc.with_ymd_and_hms(2000, 11, 25, 0, 0, 0).unwrap()
+        );
+        assert!(artist.module_ids.is_empty());
+
+        assert_eq!(
+            Artist::from_html(1, ARTIST_NOT_FOUND).unwrap_err(),
+            Error::NotFound
+        );
+    }
+
+    #[test]
+    fn follows_every_module_page() {
+        let client = fixture_client(artist_site);
+        let artist = client.get_artist(69141).unwrap();
+        assert_eq!(artist.name, "Yrde");
+        assert_eq!(artist.module_ids, [61772, 61790, 61800, 61801]);
+    }
+}
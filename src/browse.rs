@@ -0,0 +1,139 @@
+use crate::search::Listing;
+use crate::{ModArchiveClient, ModInfo, ModuleFormat, SearchPage, SearchPages, Transport};
+
+impl ModInfo {
+    /// Returns the given page (starting from 1) of the modules in `format`.
+    ///
+    /// This goes through a default [`ModArchiveClient`], use
+    /// [`ModArchiveClient::format_pages()`] to walk through all of them.
+    pub fn format_page(format: ModuleFormat, page: u32) -> Result<SearchPage, crate::Error> {
+        ModArchiveClient::new().format_page(format, page)
+    }
+
+    /// Returns the given page (starting from 1) of the modules uploaded in `year`, or only in
+    /// `month` (1 to 12) of it. Any other month is an
+    /// [`Error::InvalidArgument`](crate::Error::InvalidArgument).
+    ///
+    /// This goes through a default [`ModArchiveClient`], use
+    /// [`ModArchiveClient::uploaded_pages()`] to walk through all of them.
+    pub fn uploaded_page(
+        year: i32,
+        month: Option<u32>,
+        page: u32,
+    ) -> Result<SearchPage, crate::Error> {
+        ModArchiveClient::new().uploaded_page(year, month, page)
+    }
+}
+
+impl<T: Transport> ModArchiveClient<T> {
+    /// Same as [`ModInfo::format_page()`] but through this client's transport
+    pub fn format_page(&self, format: ModuleFormat, page: u32) -> Result<SearchPage, crate::Error> {
+        self.listing_page(&Listing::Format(format), page)
+    }
+
+    /// Returns an iterator that walks through every page of the modules in `format`
+    pub fn format_pages(&self, format: ModuleFormat) -> SearchPages<'_, T> {
+        SearchPages::new(self, Listing::Format(format), 1)
+    }
+
+    /// Same as [`ModInfo::uploaded_page()`] but through this client's transport
+    pub fn uploaded_page(
+        &self,
+        year: i32,
+        month: Option<u32>,
+        page: u32,
+    ) -> Result<SearchPage, crate::Error> {
+        self.listing_page(&Listing::Uploaded { year, month }, page)
+    }
+
+    /// Returns an iterator that walks through every page of the modules uploaded in `year`, or
+    /// only in `month` (1 to 12) of it, any other month is yielded as an error
+    pub fn uploaded_pages(&self, year: i32, month: Option<u32>) -> SearchPages<'_, T> {
+        SearchPages::new(self, Listing::Uploaded { year, month }, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{fixture_client, fixture_page, page_of};
+    use crate::{Error, ModuleFormat};
+    use chrono::Datelike;
+
+    const SPOTLIT: &str = include_str!("../tests/fixtures/module_spotlit.html");
+
+    /// Serves two pages of IT modules and of the uploads of November 2012. Only the modules on
+    /// the first page are still around, the second page's are gone.
+    fn browse_site(url: &str) -> Option<String> {
+        let listing = if url.contains("query=IT&search_type=format") {
+            include_str!("../tests/fixtures/browse_format.html")
+        } else if url.contains("request=view_by_date&year=2012&month=11") {
+            include_str!("../tests/fixtures/browse_date.html")
+        } else if url.ends_with("query=158263") {
+            return Some(SPOTLIT.to_string());
+        } else if url.ends_with("query=158300") {
+            return Some(SPOTLIT.replace("Channels: 32", "Channels: 8"));
+        } else {
+            return Some(include_str!("../tests/fixtures/module_not_found.html").to_string());
+        };
+
+        Some(fixture_page(
+            listing,
+            page_of(url),
+            &[(158263, 158301), (158300, 158302)],
+        ))
+    }
+
+    #[test]
+    fn browses_listings() {
+        let client = fixture_client(browse_site);
+
+        let page = client.format_page(ModuleFormat::It, 1).unwrap();
+        assert_eq!(page.page_count, 2);
+        assert_eq!(page.result_count, 4);
+        assert_eq!(page.results[0].id, 158263);
+        assert_eq!(page.results[1].filename, "little_loop.it");
+
+        let ids: Vec<u32> = client
+            .uploaded_pages(2012, Some(11))
+            .flat_map(|page| page.unwrap().results)
+            .map(|result| result.id)
+            .collect();
+        assert_eq!(ids, [158263, 158300, 158301, 158302]);
+
+        // months outside the year never reach the site
+        let invalid = Error::InvalidArgument {
+            name: "month",
+            value: "13".into(),
+        };
+        assert_eq!(
+            client.uploaded_page(2012, Some(13), 1).unwrap_err(),
+            invalid
+        );
+        let mut pages = client.uploaded_pages(2012, Some(0));
+        assert!(matches!(
+            pages.next(),
+            Some(Err(Error::InvalidArgument { name: "month", .. }))
+        ));
+        assert!(pages.next().is_none());
+    }
+
+    #[test]
+    fn filters_on_mod_infos() {
+        let client = fixture_client(browse_site);
+        let mut mods = client
+            .format_pages(ModuleFormat::It)
+            .mod_infos()
+            .filter_mods(|modinfo| {
+                modinfo.format == ModuleFormat::It
+                    && modinfo.channel_count > 16
+                    && modinfo.upload_date.year() == 2012
+            });
+
+        assert_eq!(mods.next().unwrap().unwrap().id, 158263);
+
+        // modules that fail to load don't end the iteration
+        assert_eq!(mods.next().unwrap().unwrap_err(), Error::NotFound);
+        assert_eq!(mods.next().unwrap().unwrap_err(), Error::NotFound);
+        assert!(mods.next().is_none());
+    }
+}
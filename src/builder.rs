@@ -0,0 +1,273 @@
+use std::time::Duration;
+
+use crate::client::Urls;
+use crate::{ModArchiveClient, RateLimiter, ResponseCache, RetryPolicy, Transport, UreqTransport};
+
+/// The user agent sent when none is set, so Mod Archive can tell who's scraping them
+const DEFAULT_USER_AGENT: &str = concat!("trackermeta/", env!("CARGO_PKG_VERSION"));
+
+/// Builder for a configured [`ModArchiveClient`], get one with [`ModArchiveClient::builder()`].
+///
+/// The timeouts, user agent and headers only apply to the default transport (they're handed
+/// to `ureq`, or `reqwest` for async clients), the base URL, retry policy, rate limiter and
+/// cache apply to every client built here.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use trackermeta::ModArchiveClient;
+///
+/// let client = ModArchiveClient::builder()
+///     .base_url("http://localhost:8080/modarchive/")
+///     .connect_timeout(Duration::from_secs(5))
+///     .read_timeout(Duration::from_secs(20))
+///     .user_agent("my-indexer/1.0")
+///     .header("X-Proxy-Token", "hunter2")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    base_url: String,
+    download_url: String,
+    connect_timeout: Duration,
+    read_timeout: Duration,
+    user_agent: String,
+    headers: Vec<(String, String)>,
+    retry: RetryPolicy,
+    limiter: Option<RateLimiter>,
+    cache: Option<ResponseCache>,
+}
+
+impl ClientBuilder {
+    /// Starts off with the defaults, `https://modarchive.org` as the base URL, a 30 second
+    /// connect timeout, a 60 second read timeout and the process-wide [`RateLimiter`]
+    pub fn new() -> Self {
+        ClientBuilder {
+            base_url: crate::client::DEFAULT_BASE_URL.into(),
+            download_url: crate::client::DEFAULT_DOWNLOAD_URL.into(),
+            connect_timeout: Duration::from_secs(30),
+            read_timeout: Duration::from_secs(60),
+            user_agent: DEFAULT_USER_AGENT.into(),
+            headers: Vec::new(),
+            retry: RetryPolicy::default(),
+            limiter: None,
+            cache: None,
+        }
+    }
+
+    /// Where `index.php` lives, change this to use a mirror or a local stand-in server
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Where module files get downloaded from, `https://api.modarchive.org/downloads.php` by
+    /// default
+    pub fn download_url(mut self, download_url: impl Into<String>) -> Self {
+        self.download_url = download_url.into();
+        self
+    }
+
+    /// How long to wait for a connection to be made
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// How long to wait on every read from the server
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// The `User-Agent` header to send, defaults to `trackermeta/<version>`
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// An extra header to send with every request, can be called multiple times
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// The [`RetryPolicy`] the client uses
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// The [`RateLimiter`] the client uses instead of the process-wide one
+    pub fn rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// Stores fetched pages in a [`ResponseCache`], there's no cache by default
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub(crate) fn ureq_transport(&self) -> UreqTransport {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(self.connect_timeout)
+            .timeout_read(self.read_timeout)
+            .user_agent(&self.user_agent)
+            .build();
+
+        self.headers
+            .iter()
+            .fold(UreqTransport::new(agent), |transport, (name, value)| {
+                transport.header(name, value)
+            })
+    }
+
+    /// Builds a client using the default [`UreqTransport`], fails if the base URL is invalid
+    pub fn build(self) -> Result<ModArchiveClient, crate::Error> {
+        let transport = self.ureq_transport();
+        self.build_with_transport(transport)
+    }
+
+    /// Builds a client around your own transport, the timeouts, user agent and headers are up
+    /// to it
+    pub fn build_with_transport<T: Transport>(
+        self,
+        transport: T,
+    ) -> Result<ModArchiveClient<T>, crate::Error> {
+        Ok(ModArchiveClient::from_parts(
+            transport,
+            Urls::new(&self.base_url, &self.download_url)?,
+            self.retry,
+            self.limiter.unwrap_or_else(RateLimiter::shared),
+            self.cache,
+        ))
+    }
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        ClientBuilder::new()
+    }
+}
+
+#[cfg(feature = "async")]
+mod nonblocking {
+    use super::ClientBuilder;
+    use crate::client::Urls;
+    use crate::nonblocking::{AsyncModArchiveClient, AsyncTransport, ReqwestTransport};
+    use crate::RateLimiter;
+
+    impl ClientBuilder {
+        pub(crate) fn reqwest_transport(&self) -> Result<ReqwestTransport, crate::Error> {
+            let mut headers = reqwest::header::HeaderMap::new();
+            for (name, value) in &self.headers {
+                let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|err| crate::Error::Transport(err.to_string()))?;
+                let value = reqwest::header::HeaderValue::from_str(value)
+                    .map_err(|err| crate::Error::Transport(err.to_string()))?;
+                headers.append(name, value);
+            }
+
+            let client = reqwest::Client::builder()
+                .connect_timeout(self.connect_timeout)
+                .read_timeout(self.read_timeout)
+                .user_agent(&self.user_agent)
+                .default_headers(headers)
+                .build()?;
+
+            Ok(ReqwestTransport::new(client))
+        }
+
+        /// Builds an async client using the default [`ReqwestTransport`], fails if the base URL
+        /// or one of the headers is invalid
+        pub fn build_async(self) -> Result<AsyncModArchiveClient, crate::Error> {
+            let transport = self.reqwest_transport()?;
+            self.build_async_with_transport(transport)
+        }
+
+        /// Builds an async client around your own transport, the timeouts, user agent and
+        /// headers are up to it
+        pub fn build_async_with_transport<T: AsyncTransport>(
+            self,
+            transport: T,
+        ) -> Result<AsyncModArchiveClient<T>, crate::Error> {
+            Ok(AsyncModArchiveClient::from_parts(
+                transport,
+                Urls::new(&self.base_url, &self.download_url)?,
+                self.retry,
+                self.limiter.unwrap_or_else(RateLimiter::shared),
+                self.cache,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ModArchiveClient, RateLimiter};
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Serves one request with the empty search fixture and hands back the request head
+    fn stand_in_server() -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/mirror", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                head.push_str(&line);
+            }
+
+            let body = include_str!("../tests/fixtures/search_empty.html");
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+
+            head
+        });
+
+        (base_url, handle)
+    }
+
+    #[test]
+    fn builder_config_reaches_the_server() {
+        let (base_url, server) = stand_in_server();
+        let client = ModArchiveClient::builder()
+            .base_url(base_url)
+            .user_agent("trackermeta-tests/1.0")
+            .header("X-Extra", "yes")
+            .rate_limiter(RateLimiter::unlimited())
+            .build()
+            .unwrap();
+
+        assert!(client.resolve_filename("a b.mod").unwrap().is_empty());
+
+        let head = server.join().unwrap().to_ascii_lowercase();
+        assert!(head.starts_with("get /mirror/index.php?request=search&query=a+b.mod&"));
+        assert!(head.contains("user-agent: trackermeta-tests/1.0\r\n"));
+        assert!(head.contains("x-extra: yes\r\n"));
+    }
+
+    #[test]
+    fn invalid_base_url() {
+        assert!(ModArchiveClient::builder()
+            .base_url("not a url")
+            .build()
+            .is_err());
+    }
+}
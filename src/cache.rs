@@ -0,0 +1,439 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Utc};
+
+use crate::{Md5Digest, ModInfo};
+
+/// A persistent on-disk cache for the pages a client fetches, every page body is stored in its
+/// own file under the cache directory keyed by the URL it came from.
+///
+/// Entries older than the TTL (a day by default) are fetched again, and with a size cap set the
+/// oldest entries get evicted once the directory grows past it. In offline mode the cache is
+/// the only thing the client looks at, stale entries are served as-is and a page that isn't
+/// cached is an [`Error::Offline`](crate::Error::Offline) instead of a request.
+///
+/// A page served from the cache keeps the time it was originally fetched as its
+/// [`ModInfo::scrape_time`](crate::ModInfo::scrape_time), use
+/// [`ModArchiveClient::refresh_mod()`](crate::ModArchiveClient::refresh_mod) when you need up to
+/// date counters like `download_count`.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use trackermeta::{ModArchiveClient, ResponseCache};
+///
+/// let client = ModArchiveClient::builder()
+///     .cache(
+///         ResponseCache::new("/tmp/trackermeta")
+///             .ttl(Duration::from_secs(7 * 24 * 60 * 60))
+///             .max_size(64 * 1024 * 1024),
+///     )
+///     .build()
+///     .unwrap();
+/// let modinfo = client.get_mod(51772).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+    max_size: Option<u64>,
+    offline: bool,
+    /// How many bytes of pages the directory holds as far as this cache and its clones know,
+    /// `None` until the first store with a size cap counts them
+    size: Arc<Mutex<Option<u64>>>,
+}
+
+/// The file extension of cache entries, anything else in the directory is left alone
+const ENTRY_EXTENSION: &str = "page";
+
+/// Numbers every write in this process so no two of them share a temporary file, even for the
+/// same URL
+static PARTIAL_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A page body read back from the cache
+pub(crate) struct CachedPage {
+    pub(crate) body: String,
+    pub(crate) fetched: DateTime<Utc>,
+}
+
+impl CachedPage {
+    /// Parses the page as the module page of `mod_id`, scraped when the page was fetched
+    pub(crate) fn into_mod(self, mod_id: u32) -> Result<ModInfo, crate::Error> {
+        let mut modinfo = ModInfo::from_html(mod_id, &self.body)?;
+        modinfo.scrape_time = self.fetched;
+        Ok(modinfo)
+    }
+}
+
+// The cache side of a request, shared by the blocking and the async client so the only thing
+// they do themselves is the fetching in between
+
+/// Looks `url` up in a client's cache before fetching it, `None` means it has to be fetched
+/// (there's no cache, it's a miss or `refresh` is set) which in offline mode is an error
+pub(crate) fn lookup(
+    cache: Option<&ResponseCache>,
+    url: &str,
+    refresh: bool,
+) -> Result<Option<CachedPage>, crate::Error> {
+    let cache = match cache {
+        Some(cache) => cache,
+        None => return Ok(None),
+    };
+    if let Some(page) = cache.get(url).filter(|_| !refresh) {
+        return Ok(Some(page));
+    }
+    ensure_online(Some(cache))?;
+    Ok(None)
+}
+
+/// For requests that never come from the cache (downloads, redirects), they're an
+/// [`Error::Offline`](crate::Error::Offline) in offline mode
+pub(crate) fn ensure_online(cache: Option<&ResponseCache>) -> Result<(), crate::Error> {
+    match cache {
+        Some(cache) if cache.is_offline() => Err(crate::Error::Offline),
+        _ => Ok(()),
+    }
+}
+
+/// Stores a page a client just fetched, if it has a cache
+pub(crate) fn store(cache: Option<&ResponseCache>, url: &str, body: &str, fetched: DateTime<Utc>) {
+    if let Some(cache) = cache {
+        // a cache that can't be written to shouldn't fail the request, it just won't help
+        let _ = cache.put(url, body, fetched);
+    }
+}
+
+impl ResponseCache {
+    /// A cache living in `dir` with a TTL of one day and no size cap, the directory is created
+    /// when the first page gets stored
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        ResponseCache {
+            dir: dir.into(),
+            ttl: Duration::from_secs(24 * 60 * 60),
+            max_size: None,
+            offline: false,
+            size: Arc::default(),
+        }
+    }
+
+    /// How long a stored page stays fresh, `Duration::MAX` keeps pages forever
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// The most bytes of pages to keep around, the oldest ones are evicted past this
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Whether to never touch the network and answer everything from the cache
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// The directory the pages are stored in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Whether the cache is in offline mode
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Deletes every stored page
+    pub fn clear(&self) -> Result<(), crate::Error> {
+        let mut size = self.size.lock().unwrap_or_else(PoisonError::into_inner);
+        *size = None;
+        for (path, _) in self.entries()? {
+            remove_entry(&path)?;
+        }
+        Ok(())
+    }
+
+    fn entry_path(&self, url: &str) -> PathBuf {
+        let key = Md5Digest(md5::compute(url).0);
+        self.dir.join(format!("{}.{}", key, ENTRY_EXTENSION))
+    }
+
+    /// Looks up the page for `url`, expired pages only count in offline mode
+    pub(crate) fn get(&self, url: &str) -> Option<CachedPage> {
+        let path = self.entry_path(url);
+        let stored = std::fs::metadata(&path)
+            .and_then(|meta| meta.modified())
+            .ok()?;
+
+        let age = SystemTime::now()
+            .duration_since(stored)
+            .unwrap_or(Duration::ZERO);
+        if age >= self.ttl && !self.offline {
+            return None;
+        }
+
+        Some(CachedPage {
+            body: std::fs::read_to_string(&path).ok()?,
+            fetched: stored.into(),
+        })
+    }
+
+    /// Stores the page for `url` that was fetched at `fetched`, then evicts the oldest pages if
+    /// the cache went over its cap
+    pub(crate) fn put(
+        &self,
+        url: &str,
+        body: &str,
+        fetched: DateTime<Utc>,
+    ) -> Result<(), crate::Error> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        // written next to the entry and renamed over it so readers never see half a page, the
+        // modification time doubles as the fetch time
+        let path = self.entry_path(url);
+        let partial = partial_path(&path);
+        let replaced = write_entry(&partial, &path, body, fetched);
+        if replaced.is_err() {
+            let _ = std::fs::remove_file(&partial);
+        }
+        let replaced = replaced?;
+
+        if let Some(max_size) = self.max_size {
+            self.account(body.len() as u64, replaced, max_size)?;
+        }
+        Ok(())
+    }
+
+    /// Keeps the running size up to date after a store of `added` bytes that replaced an entry
+    /// of `replaced` bytes, the directory is only gone through on the first store and once the
+    /// cap is passed
+    fn account(&self, added: u64, replaced: u64, max_size: u64) -> Result<(), crate::Error> {
+        let mut size = self.size.lock().unwrap_or_else(PoisonError::into_inner);
+        let total = match *size {
+            Some(total) => total.saturating_sub(replaced) + added,
+            None => self.entries()?.iter().map(|(_, meta)| meta.len()).sum(),
+        };
+
+        *size = Some(if total > max_size {
+            self.evict(max_size)?
+        } else {
+            total
+        });
+        Ok(())
+    }
+
+    /// Evicts the oldest pages until the cache fits in `max_size`, returns the size it ended
+    /// up at. The directory is counted again here since other processes may share it.
+    fn evict(&self, max_size: u64) -> Result<u64, crate::Error> {
+        let mut entries = self.entries()?;
+        let mut total: u64 = entries.iter().map(|(_, meta)| meta.len()).sum();
+
+        entries.sort_by_key(|(_, meta)| meta.modified().unwrap_or(SystemTime::UNIX_EPOCH));
+        for (path, meta) in entries {
+            if total <= max_size {
+                break;
+            }
+            remove_entry(&path)?;
+            total -= meta.len();
+        }
+        Ok(total)
+    }
+
+    fn entries(&self) -> Result<Vec<(PathBuf, std::fs::Metadata)>, crate::Error> {
+        let dir = match std::fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut entries = Vec::new();
+        for entry in dir {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == ENTRY_EXTENSION) {
+                entries.push((path.clone(), std::fs::metadata(path)?));
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// A temporary file next to the entry at `path` that no other write uses, processes sharing
+/// the directory are told apart by their ID and writes in this one by a counter
+fn partial_path(path: &Path) -> PathBuf {
+    path.with_extension(format!(
+        "{}-{}.partial",
+        std::process::id(),
+        PARTIAL_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Writes `body` to `partial` and renames it over the entry at `path`, returns the size of the
+/// entry it replaced (0 if there wasn't one)
+fn write_entry(
+    partial: &Path,
+    path: &Path,
+    body: &str,
+    fetched: DateTime<Utc>,
+) -> Result<u64, crate::Error> {
+    let mut file = std::fs::File::create(partial)?;
+    file.write_all(body.as_bytes())?;
+    file.set_modified(fetched.into())?;
+    drop(file);
+
+    let replaced = std::fs::metadata(path).map_or(0, |meta| meta.len());
+    std::fs::rename(partial, path)?;
+    Ok(replaced)
+}
+
+/// Removes a cache entry, one that's already gone (another process evicting it) is fine
+fn remove_entry(path: &Path) -> Result<(), crate::Error> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, ModArchiveClient, RateLimiter, Response, ResponseCache, Transport};
+    use chrono::Utc;
+    use std::cell::Cell;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    /// Serves the module fixture and counts how many times it was asked for anything
+    #[derive(Default)]
+    struct CountingTransport(Cell<u32>);
+
+    impl Transport for CountingTransport {
+        fn fetch(&self, _url: &str) -> Result<Response, Error> {
+            self.0.set(self.0.get() + 1);
+            Ok(Response::new(
+                200,
+                include_str!("../tests/fixtures/module.html"),
+            ))
+        }
+    }
+
+    /// A fresh cache directory for one test
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("trackermeta-cache-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn client(cache: ResponseCache) -> ModArchiveClient<CountingTransport> {
+        ModArchiveClient::with_transport(CountingTransport::default())
+            .with_rate_limiter(RateLimiter::unlimited())
+            .with_cache(cache)
+    }
+
+    #[test]
+    fn serves_from_cache() {
+        let dir = cache_dir("serves");
+        let client = client(ResponseCache::new(&dir));
+
+        let first = client.get_mod(61772).unwrap();
+        let second = client.get_mod(61772).unwrap();
+        assert_eq!(client.transport().0.get(), 1);
+        assert_eq!(first, second);
+
+        // refreshing always goes to the network and updates the cache
+        let refreshed = client.refresh_mod(61772).unwrap();
+        assert_eq!(client.transport().0.get(), 2);
+        assert!(refreshed.scrape_time >= first.scrape_time);
+        assert_eq!(client.get_mod(61772).unwrap(), refreshed);
+        assert_eq!(client.transport().0.get(), 2);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn expired_pages_are_fetched_again() {
+        let dir = cache_dir("expired");
+        let client = client(ResponseCache::new(&dir).ttl(Duration::ZERO));
+
+        client.get_mod(61772).unwrap();
+        client.get_mod(61772).unwrap();
+        assert_eq!(client.transport().0.get(), 2);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn offline_mode() {
+        let dir = cache_dir("offline");
+        let offline = client(ResponseCache::new(&dir).ttl(Duration::ZERO).offline(true));
+        assert_eq!(offline.get_mod(61772).unwrap_err(), Error::Offline);
+        assert_eq!(offline.refresh_mod(61772).unwrap_err(), Error::Offline);
+
+        client(ResponseCache::new(&dir)).get_mod(61772).unwrap();
+
+        // stale pages are still good enough when there's no network
+        assert_eq!(offline.get_mod(61772).unwrap().id, 61772);
+        assert_eq!(offline.transport().0.get(), 0);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn size_cap_evicts_oldest() {
+        let dir = cache_dir("evicts");
+        let page = include_str!("../tests/fixtures/module.html");
+        let cache = ResponseCache::new(&dir).max_size(page.len() as u64 * 2);
+
+        // oldest first, the fetch time is what decides the age of an entry
+        for (n, hours_ago) in [(1, 3), (2, 2), (3, 1)] {
+            let url = format!("https://example.com/{}", n);
+            let fetched = Utc::now() - chrono::Duration::hours(hours_ago);
+            cache.put(&url, page, fetched).unwrap();
+        }
+
+        assert!(cache.get("https://example.com/1").is_none());
+        assert!(cache.get("https://example.com/2").is_some());
+        assert!(cache.get("https://example.com/3").is_some());
+
+        // replacing an entry doesn't count it twice
+        cache
+            .put("https://example.com/3", page, Utc::now())
+            .unwrap();
+        assert!(cache.get("https://example.com/2").is_some());
+
+        cache.clear().unwrap();
+        assert!(cache.get("https://example.com/3").is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn failed_writes_leave_nothing_behind() {
+        let dir = cache_dir("failed");
+        let cache = ResponseCache::new(&dir);
+
+        // a directory where the entry should go can't be renamed over
+        let url = "https://example.com/1";
+        std::fs::create_dir_all(cache.entry_path(url).join("taken")).unwrap();
+        assert!(matches!(
+            cache.put(url, "page", Utc::now()),
+            Err(Error::Io(_))
+        ));
+
+        let leftovers: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "partial"))
+            .collect();
+        assert!(leftovers.is_empty());
+
+        // and every write gets its own temporary file
+        let entry = cache.entry_path(url);
+        assert_ne!(super::partial_path(&entry), super::partial_path(&entry));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,651 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+
+use crate::cache::{self, CachedPage};
+use crate::search::Listing;
+use crate::types::HashingWriter;
+use crate::{
+    parse, ClientBuilder, Md5Digest, ModInfo, ModSearch, RandomFilter, RateLimiter, Response,
+    ResponseCache, RetryPolicy, SearchPage, SearchPages, SearchQuery, Transport, UreqTransport,
+};
+
+pub(crate) const DEFAULT_BASE_URL: &str = "https://modarchive.org/";
+pub(crate) const DEFAULT_DOWNLOAD_URL: &str = "https://api.modarchive.org/downloads.php";
+
+/// The most [`ModArchiveClient::download()`] allocates up front, the size comes from the page
+/// (or from wherever the [`ModInfo`] was deserialized from) so it can't be trusted with more
+const MAX_DOWNLOAD_PREALLOCATION: u64 = 64 * 1024 * 1024;
+
+/// Builds the URLs for every page the crate scrapes, relative to a base URL. Every key and
+/// value gets percent-encoded so anything can go in them.
+#[derive(Debug, Clone)]
+pub(crate) struct Urls {
+    index: url::Url,
+    download: url::Url,
+}
+
+impl Urls {
+    pub(crate) fn new(base_url: &str, download_url: &str) -> Result<Urls, crate::Error> {
+        let invalid = |err: url::ParseError| {
+            crate::Error::Transport(format!("invalid base url {:?}: {}", base_url, err))
+        };
+        let download = url::Url::parse(download_url).map_err(|err| {
+            crate::Error::Transport(format!("invalid download url {:?}: {}", download_url, err))
+        })?;
+
+        // without the trailing slash joining would replace the last path segment
+        let mut base = url::Url::parse(base_url).map_err(invalid)?;
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+
+        Ok(Urls {
+            index: base.join("index.php").map_err(invalid)?,
+            download,
+        })
+    }
+
+    /// A URL to `index.php` with `params` as the query string
+    pub(crate) fn index<'a>(&self, params: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+        let mut url = self.index.clone();
+        url.query_pairs_mut().extend_pairs(params);
+        url.into()
+    }
+
+    pub(crate) fn mod_page(&self, mod_id: u32) -> String {
+        self.index([
+            ("request", "view_by_moduleid"),
+            ("query", mod_id.to_string().as_str()),
+        ])
+    }
+
+    /// Where the module file itself gets downloaded from, same as
+    /// [`ModInfo::get_download_link()`] minus the filename fragment
+    pub(crate) fn download(&self, mod_id: u32) -> String {
+        let mut url = self.download.clone();
+        url.query_pairs_mut()
+            .append_pair("moduleid", &mod_id.to_string());
+        url.into()
+    }
+
+    pub(crate) fn listing(&self, listing: &Listing, page: u32) -> String {
+        self.index(
+            listing
+                .params(page)
+                .iter()
+                .map(|(key, value)| (*key, value.as_str())),
+        )
+    }
+
+    pub(crate) fn reviews(&self, mod_id: u32) -> String {
+        self.index([
+            ("request", "view_module_reviews"),
+            ("query", mod_id.to_string().as_str()),
+        ])
+    }
+
+    pub(crate) fn comments(&self, mod_id: u32, page: u32) -> String {
+        self.index([
+            ("request", "view_module_comments"),
+            ("query", mod_id.to_string().as_str()),
+            ("page", page.to_string().as_str()),
+        ])
+    }
+
+    /// The random module link, it redirects to the page of a random module
+    pub(crate) fn random(&self, filter: Option<&RandomFilter>) -> String {
+        let mut params = vec![("request", "view_random".to_string())];
+        params.extend(filter.map(RandomFilter::param));
+        self.index(params.iter().map(|(key, value)| (*key, value.as_str())))
+    }
+
+    pub(crate) fn profile(&self, member_id: u32) -> String {
+        self.index([
+            ("request", "view_profile"),
+            ("query", member_id.to_string().as_str()),
+        ])
+    }
+}
+
+impl Default for Urls {
+    fn default() -> Self {
+        Urls::new(DEFAULT_BASE_URL, DEFAULT_DOWNLOAD_URL).expect("the default urls are valid")
+    }
+}
+
+/// Non-success statuses become [`Error::Status`](crate::Error::Status)
+pub(crate) fn check_status(status: u16) -> Result<(), crate::Error> {
+    if !(200..300).contains(&status) {
+        return Err(crate::Error::Status(status));
+    }
+
+    Ok(())
+}
+
+/// Turns a raw response into a page body, non-success statuses become
+/// [`Error::Status`](crate::Error::Status).
+pub(crate) fn page_body(response: Response) -> Result<String, crate::Error> {
+    check_status(response.status)?;
+    response.text()
+}
+
+/// The module a redirect (like the random module link) landed on, the page gets stored in
+/// `cache` as that module's page
+pub(crate) fn landed_mod(
+    cache: Option<&ResponseCache>,
+    urls: &Urls,
+    page: CachedPage,
+) -> Result<ModInfo, crate::Error> {
+    let mod_id = parse::mod_page_id(&page.body)?;
+    cache::store(cache, &urls.mod_page(mod_id), &page.body, page.fetched);
+    page.into_mod(mod_id)
+}
+
+/// Checks the MD5 of a downloaded module against the one scraped from its page
+pub(crate) fn verify_download(modinfo: &ModInfo, actual: Md5Digest) -> Result<(), crate::Error> {
+    if actual != modinfo.md5 {
+        return Err(crate::Error::ChecksumMismatch {
+            expected: modinfo.md5,
+            actual,
+        });
+    }
+
+    Ok(())
+}
+
+/// A client that owns a [`Transport`] and does all of the fetching and scraping through it,
+/// the free functions like [`ModInfo::get()`] just use a default one of these. Every request
+/// goes through the client's [`RateLimiter`] and failed ones are retried according to its
+/// [`RetryPolicy`].
+///
+/// Use [`ModArchiveClient::builder()`] to point it somewhere else or change the timeouts, user
+/// agent and headers.
+///
+/// ```rust
+/// use trackermeta::{ModArchiveClient, UreqTransport};
+///
+/// let agent = ureq::AgentBuilder::new().user_agent("my-indexer/1.0").build();
+/// let client = ModArchiveClient::with_transport(UreqTransport::new(agent));
+/// let modinfo = client.get_mod(51772).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModArchiveClient<T = UreqTransport> {
+    transport: T,
+    urls: Urls,
+    retry: RetryPolicy,
+    limiter: RateLimiter,
+    cache: Option<ResponseCache>,
+}
+
+impl ModArchiveClient {
+    /// Creates a client with all of the defaults, see [`ClientBuilder::new()`] for what they
+    /// are. It shares the process-wide [`RateLimiter`] with the free functions and every other
+    /// client made this way.
+    pub fn new() -> Self {
+        ClientBuilder::new()
+            .build()
+            .expect("the default client config is always valid")
+    }
+
+    /// Starts building a client with a custom base URL, timeouts, user agent or headers
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+}
+
+impl Default for ModArchiveClient {
+    fn default() -> Self {
+        ModArchiveClient::new()
+    }
+}
+
+impl<T: Transport> ModArchiveClient<T> {
+    /// Creates a client that does its requests through `transport`, it gets its own
+    /// [`RateLimiter`] with the default rate of 1 request per second.
+    pub fn with_transport(transport: T) -> Self {
+        ModArchiveClient::from_parts(
+            transport,
+            Urls::default(),
+            RetryPolicy::default(),
+            RateLimiter::default(),
+            None,
+        )
+    }
+
+    pub(crate) fn from_parts(
+        transport: T,
+        urls: Urls,
+        retry: RetryPolicy,
+        limiter: RateLimiter,
+        cache: Option<ResponseCache>,
+    ) -> Self {
+        ModArchiveClient {
+            transport,
+            urls,
+            retry,
+            limiter,
+            cache,
+        }
+    }
+
+    /// The transport this client is using
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Uses `policy` for retrying failed requests instead of the default [`RetryPolicy`]
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// The retry policy this client is using
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// Throttles this client with `limiter` instead, pass a clone of the same limiter to several
+    /// clients to keep them under one limit together
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// The rate limiter this client is using
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+
+    /// Stores fetched pages in `cache` and answers from it when it can
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// The response cache this client is using, if any
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+
+    pub(crate) fn urls(&self) -> &Urls {
+        &self.urls
+    }
+
+    /// Fetches a page through the cache, the limiter and the retry policy
+    pub(crate) fn fetch_page(&self, url: &str) -> Result<String, crate::Error> {
+        Ok(self.fetch_cached(url, false)?.body)
+    }
+
+    /// Goes through the cache first unless `refresh` is set, fresh pages get stored in it
+    fn fetch_cached(&self, url: &str, refresh: bool) -> Result<CachedPage, crate::Error> {
+        if let Some(page) = cache::lookup(self.cache.as_ref(), url, refresh)? {
+            return Ok(page);
+        }
+
+        let fetched = Utc::now();
+        let body = self.fetch_uncached(url)?;
+        cache::store(self.cache.as_ref(), url, &body, fetched);
+
+        Ok(CachedPage { body, fetched })
+    }
+
+    fn fetch_uncached(&self, url: &str) -> Result<String, crate::Error> {
+        let mut attempt = 1;
+
+        loop {
+            self.limiter.wait();
+            match self.transport.fetch(url).and_then(page_body) {
+                Err(err) if self.retry.should_retry(&err, attempt) => {
+                    std::thread::sleep(self.retry.delay(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Same as [`ModInfo::get()`] but through this client's transport
+    pub fn get_mod(&self, mod_id: u32) -> Result<ModInfo, crate::Error> {
+        self.fetch_mod(mod_id, false)
+    }
+
+    /// Fetches the module page again even if it's cached and updates the cache with it, use
+    /// this for up to date counters like [`ModInfo::download_count`]. Without a cache this is
+    /// the same as [`get_mod()`](ModArchiveClient::get_mod).
+    pub fn refresh_mod(&self, mod_id: u32) -> Result<ModInfo, crate::Error> {
+        self.fetch_mod(mod_id, true)
+    }
+
+    fn fetch_mod(&self, mod_id: u32, refresh: bool) -> Result<ModInfo, crate::Error> {
+        self.fetch_cached(&self.urls.mod_page(mod_id), refresh)?
+            .into_mod(mod_id)
+    }
+
+    /// Fetches a link that redirects to a module page, like the random module one. The link
+    /// itself is never cached but the module page it lands on is stored as that module's page.
+    pub(crate) fn fetch_landing_mod(&self, url: &str) -> Result<ModInfo, crate::Error> {
+        cache::ensure_online(self.cache.as_ref())?;
+
+        let fetched = Utc::now();
+        let body = self.fetch_uncached(url)?;
+        landed_mod(
+            self.cache.as_ref(),
+            &self.urls,
+            CachedPage { body, fetched },
+        )
+    }
+
+    /// Downloads the module file and checks it against the MD5 scraped from its page, a file
+    /// that doesn't match is an [`Error::ChecksumMismatch`](crate::Error::ChecksumMismatch).
+    /// Downloads skip the cache but still go through the rate limiter, in offline mode they're
+    /// an [`Error::Offline`](crate::Error::Offline).
+    pub fn download(&self, modinfo: &ModInfo) -> Result<Vec<u8>, crate::Error> {
+        let mut bytes = Vec::with_capacity(modinfo.size.min(MAX_DOWNLOAD_PREALLOCATION) as usize);
+        self.download_to(modinfo, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Same as [`download()`](ModArchiveClient::download) but streams the file into `writer`
+    /// and returns how many bytes were written, the checksum can only be checked at the end so
+    /// on a mismatch `writer` has already gotten the bad file.
+    pub fn download_to<W: Write>(&self, modinfo: &ModInfo, writer: W) -> Result<u64, crate::Error> {
+        cache::ensure_online(self.cache.as_ref())?;
+
+        let url = self.urls.download(modinfo.id);
+        let mut out = HashingWriter::new(writer);
+        let mut attempt = 1;
+
+        loop {
+            self.limiter.wait();
+
+            // once some of the file is out the door retrying would write it twice
+            let written = out.written();
+            match self
+                .transport
+                .fetch_to(&url, &mut out)
+                .and_then(check_status)
+            {
+                Err(err) if out.written() == written && self.retry.should_retry(&err, attempt) => {
+                    std::thread::sleep(self.retry.delay(attempt));
+                    attempt += 1;
+                }
+                result => break result?,
+            }
+        }
+        out.flush()?;
+
+        let written = out.written();
+        verify_download(modinfo, out.digest())?;
+        Ok(written)
+    }
+
+    /// Same as [`download()`](ModArchiveClient::download) but saves the file to `path`, it's
+    /// written to a temporary file next to it first so `path` only ever holds a verified
+    /// module.
+    pub fn download_to_path(
+        &self,
+        modinfo: &ModInfo,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, crate::Error> {
+        let path = path.as_ref();
+        let io_error =
+            |err: std::io::Error| crate::Error::Io(format!("{}: {}", path.display(), err));
+
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        let partial = PathBuf::from(partial);
+
+        let file = std::fs::File::create(&partial).map_err(io_error)?;
+        let written = self
+            .download_to(modinfo, std::io::BufWriter::new(file))
+            .and_then(|written| {
+                std::fs::rename(&partial, path).map_err(io_error)?;
+                Ok(written)
+            });
+
+        if written.is_err() {
+            let _ = std::fs::remove_file(&partial);
+        }
+        written
+    }
+
+    /// Same as [`ModInfo::resolve_filename()`] but through this client's transport
+    pub fn resolve_filename(&self, filename: &str) -> Result<Vec<ModSearch>, crate::Error> {
+        Ok(self.resolve_filename_page(filename, 1)?.results)
+    }
+
+    /// Searches for your string on Mod Archive and returns the given page of results (starting
+    /// from 1), the [`SearchPage`] also tells you how many pages and results there are in total.
+    pub fn resolve_filename_page(
+        &self,
+        filename: &str,
+        page: u32,
+    ) -> Result<SearchPage, crate::Error> {
+        self.search(&SearchQuery::new(filename).page(page))
+    }
+
+    /// Returns an iterator that walks through every page of results for your string, use this
+    /// when the first 40 results aren't enough.
+    ///
+    /// ```rust
+    /// use trackermeta::{ModArchiveClient, ModSearch};
+    ///
+    /// let client = ModArchiveClient::new();
+    /// let everything: Vec<ModSearch> = client
+    ///     .resolve_filename_pages("intro.mod")
+    ///     .map(|page| page.map(|page| page.results))
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap()
+    ///     .into_iter()
+    ///     .flatten()
+    ///     .collect();
+    /// ```
+    pub fn resolve_filename_pages(&self, filename: &str) -> SearchPages<'_, T> {
+        self.search_pages(SearchQuery::new(filename))
+    }
+
+    /// Same as [`ModInfo::search()`] but through this client's transport
+    pub fn search(&self, query: &SearchQuery) -> Result<SearchPage, crate::Error> {
+        self.listing_page(&Listing::Search(query.clone()), query.page_number())
+    }
+
+    /// Returns an iterator that walks through every page of results for `query`, starting
+    /// from the page set on it
+    pub fn search_pages(&self, query: SearchQuery) -> SearchPages<'_, T> {
+        let first_page = query.page_number();
+        SearchPages::new(self, Listing::Search(query), first_page)
+    }
+
+    pub(crate) fn listing_page(
+        &self,
+        listing: &Listing,
+        page: u32,
+    ) -> Result<SearchPage, crate::Error> {
+        listing.check()?;
+        SearchPage::from_html(&self.fetch_page(&self.urls.listing(listing, page))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Urls, DEFAULT_DOWNLOAD_URL};
+    use crate::{
+        Error, Md5Digest, ModArchiveClient, ModInfo, RateLimiter, Response, RetryPolicy, Transport,
+    };
+    use std::cell::RefCell;
+
+    struct StatusTransport(u16);
+
+    impl Transport for StatusTransport {
+        fn fetch(&self, _url: &str) -> Result<Response, Error> {
+            Ok(Response::new(self.0, "<html></html>"))
+        }
+    }
+
+    #[test]
+    fn status_is_surfaced() {
+        let client = ModArchiveClient::with_transport(StatusTransport(503))
+            .with_retry_policy(RetryPolicy::none());
+        assert_eq!(
+            client.resolve_filename("a.mod").unwrap_err(),
+            Error::Status(503)
+        );
+    }
+
+    #[test]
+    fn missing_page_is_not_found() {
+        let client = ModArchiveClient::with_transport(StatusTransport(200));
+        assert_eq!(
+            client.resolve_filename("a.mod").unwrap_err(),
+            Error::NotFound
+        );
+    }
+
+    /// Remembers the last URL it was asked for and serves an empty search page
+    #[derive(Default)]
+    struct RecordingTransport(RefCell<String>);
+
+    impl Transport for RecordingTransport {
+        fn fetch(&self, url: &str) -> Result<Response, Error> {
+            *self.0.borrow_mut() = url.into();
+            Ok(Response::new(
+                200,
+                include_str!("../tests/fixtures/search_empty.html"),
+            ))
+        }
+    }
+
+    #[test]
+    fn search_is_percent_encoded() {
+        let client = ModArchiveClient::with_transport(RecordingTransport::default())
+            .with_rate_limiter(RateLimiter::unlimited());
+
+        for filename in [
+            "space odyssey.mod",
+            "rock&roll.xm",
+            "track#1.it",
+            "c++.s3m",
+            "ääkkönen_ø.mod",
+            "100%=fun?.xm",
+        ] {
+            client.resolve_filename(filename).unwrap();
+
+            let url = url::Url::parse(&client.transport().0.borrow()).unwrap();
+            assert_eq!(url.fragment(), None);
+
+            let queries: Vec<String> = url
+                .query_pairs()
+                .filter(|(key, _)| key == "query")
+                .map(|(_, value)| value.into_owned())
+                .collect();
+            assert_eq!(queries, [filename]);
+        }
+    }
+
+    #[test]
+    fn mod_page_url() {
+        assert_eq!(
+            Urls::default().mod_page(61772),
+            "https://modarchive.org/index.php?request=view_by_moduleid&query=61772"
+        );
+        assert_eq!(
+            Urls::default().download(61772),
+            "https://api.modarchive.org/downloads.php?moduleid=61772"
+        );
+        assert_eq!(
+            Urls::new("http://localhost:8080/mirror", DEFAULT_DOWNLOAD_URL)
+                .unwrap()
+                .mod_page(1),
+            "http://localhost:8080/mirror/index.php?request=view_by_moduleid&query=1"
+        );
+    }
+
+    const MODULE_FILE: &[u8] = b"Extended Module: 7th Dance";
+
+    /// Serves a fake module file for downloads
+    struct DownloadTransport;
+
+    impl Transport for DownloadTransport {
+        fn fetch(&self, url: &str) -> Result<Response, Error> {
+            assert!(url.starts_with(DEFAULT_DOWNLOAD_URL));
+            Ok(Response::new(200, MODULE_FILE))
+        }
+    }
+
+    fn downloadable(md5: Md5Digest) -> ModInfo {
+        let mut modinfo =
+            ModInfo::from_html(61772, include_str!("../tests/fixtures/module.html")).unwrap();
+        modinfo.md5 = md5;
+        modinfo
+    }
+
+    #[test]
+    fn download_is_verified() {
+        let client = ModArchiveClient::with_transport(DownloadTransport)
+            .with_rate_limiter(RateLimiter::unlimited());
+
+        let good = downloadable(Md5Digest::compute(MODULE_FILE));
+        assert_eq!(client.download(&good).unwrap(), MODULE_FILE);
+
+        // the size is only a hint, a bogus one mustn't take the process down
+        let mut huge = good.clone();
+        huge.size = u64::MAX;
+        assert_eq!(client.download(&huge).unwrap(), MODULE_FILE);
+
+        let bad = downloadable(Md5Digest([0; 16]));
+        assert_eq!(
+            client.download(&bad).unwrap_err(),
+            Error::ChecksumMismatch {
+                expected: Md5Digest([0; 16]),
+                actual: Md5Digest::compute(MODULE_FILE),
+            }
+        );
+    }
+
+    #[test]
+    fn download_writer_errors_are_io() {
+        /// A writer for a disk that's always full
+        struct FullDisk;
+
+        impl std::io::Write for FullDisk {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("disk full"))
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let client = ModArchiveClient::with_transport(DownloadTransport)
+            .with_rate_limiter(RateLimiter::unlimited());
+        let good = downloadable(Md5Digest::compute(MODULE_FILE));
+        assert_eq!(
+            client.download_to(&good, FullDisk).unwrap_err(),
+            Error::Io("disk full".into())
+        );
+    }
+
+    #[test]
+    fn download_to_path_only_keeps_verified_files() {
+        let client = ModArchiveClient::with_transport(DownloadTransport)
+            .with_rate_limiter(RateLimiter::unlimited());
+        let dir = std::env::temp_dir().join(format!("trackermeta-download-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("7th_dance.xm");
+
+        let bad = downloadable(Md5Digest([0; 16]));
+        assert!(client.download_to_path(&bad, &path).is_err());
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        let good = downloadable(Md5Digest::compute(MODULE_FILE));
+        let written = client.download_to_path(&good, &path).unwrap();
+        assert_eq!(written, MODULE_FILE.len() as u64);
+        assert_eq!(std::fs::read(&path).unwrap(), MODULE_FILE);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
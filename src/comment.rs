@@ -0,0 +1,119 @@
+use chrono::prelude::{DateTime, Utc};
+
+use crate::search::PageWalk;
+use crate::{parse, Member, ModArchiveClient, ModInfo, Transport};
+
+/// A comment left on a module page, get them with [`ModInfo::comments()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Comment {
+    /// The member who posted the comment
+    pub author: Member,
+    /// When the comment was posted, down to the minute
+    pub time: DateTime<Utc>,
+    /// The time as the site shows it, for example `Wed 4th Apr 2001 21:07`
+    pub time_text: String,
+    /// The comment itself with the HTML entities decoded
+    pub text: String,
+}
+
+/// One page of a module's comments
+pub(crate) struct CommentPage {
+    pub(crate) comments: Vec<Comment>,
+    pub(crate) page: u32,
+    pub(crate) page_count: u32,
+}
+
+impl Comment {
+    /// Does the same extraction as [`ModInfo::comments()`] but on a single comments page you
+    /// already have, nothing is fetched so only the comments on that page are returned
+    pub fn from_html(html: &str) -> Result<Vec<Comment>, crate::Error> {
+        parse::comments(html).map(|page| page.comments)
+    }
+}
+
+impl ModInfo {
+    /// Gets every comment on the module with ID `mod_id`, oldest first, following the comment
+    /// pages until the last one so that's one request per page.
+    ///
+    /// This goes through a default [`ModArchiveClient`], use [`ModArchiveClient::get_comments()`]
+    /// if you want to bring your own [`Transport`].
+    pub fn comments(mod_id: u32) -> Result<Vec<Comment>, crate::Error> {
+        ModArchiveClient::new().get_comments(mod_id)
+    }
+}
+
+impl<T: Transport> ModArchiveClient<T> {
+    /// Same as [`ModInfo::comments()`] but through this client's transport
+    pub fn get_comments(&self, mod_id: u32) -> Result<Vec<Comment>, crate::Error> {
+        let mut comments = Vec::new();
+        let mut walk = PageWalk::new(1);
+        while let Some(page) = walk.next_page() {
+            let current = parse::comments(&self.fetch_page(&self.urls().comments(mod_id, page))?)?;
+            if walk.record(current.page, current.page_count) {
+                comments.extend(current.comments);
+            }
+        }
+        Ok(comments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{comment_pages, fixture_client};
+    use crate::{Comment, Error, Member};
+    use chrono::prelude::{TimeZone, Utc};
+
+    const COMMENTS: &str = include_str!("../tests/fixtures/module_comments.html");
+
+    #[test]
+    fn fixture_comments() {
+        let comments = Comment::from_html(COMMENTS).unwrap();
+        assert_eq!(comments.len(), 2);
+
+        assert_eq!(
+            comments[0].author,
+            Member {
+                id: 1234,
+                name: "Sapphire".into()
+            }
+        );
+        assert_eq!(
+            comments[0].time,
+            Utc.with_ymd_and_hms(2001, 4, 4, 21, 7, 0).unwrap()
+        );
+        assert_eq!(comments[0].time_text, "Wed 4th Apr 2001 21:07");
+        assert_eq!(comments[0].text, "\"7th Dance\" is still my favourite <3");
+
+        assert_eq!(comments[1].author.name, "Rez & Co");
+        assert_eq!(
+            comments[1].text,
+            "Found this on an old demo CD,\nglad it's archived here."
+        );
+
+        assert_eq!(
+            Comment::from_html(include_str!("../tests/fixtures/module_not_found.html"))
+                .unwrap_err(),
+            Error::NotFound
+        );
+    }
+
+    #[test]
+    fn follows_every_comment_page() {
+        let client = fixture_client(comment_pages);
+        let authors: Vec<u32> = client
+            .get_comments(61772)
+            .unwrap()
+            .into_iter()
+            .map(|comment| comment.author.id)
+            .collect();
+        assert_eq!(authors, [1234, 9012, 3456, 7890]);
+    }
+
+    #[test]
+    fn stops_when_the_page_does_not_move() {
+        // the site ignores the page number and always answers with the first page
+        let client = fixture_client(|_| Some(COMMENTS.to_string()));
+        assert_eq!(client.get_comments(61772).unwrap().len(), 2);
+    }
+}
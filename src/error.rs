@@ -0,0 +1,158 @@
+use std::fmt;
+
+/// Error enum for functions in the crate that return a [`Result`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The module (or search) doesn't exist on Mod Archive
+    NotFound,
+    /// The request timed out before a response came back
+    Timeout,
+    /// The connection to the server failed, was refused or got reset
+    Connection(String),
+    /// Any other error from the HTTP backend (bad URL, TLS, proxy and so on)
+    Transport(String),
+    /// The server answered with a non-success HTTP status code
+    Status(u16),
+    /// The response body couldn't be read or decoded into a usable document
+    Decode(String),
+    /// An element that should always be on the page wasn't there, the field
+    /// it was supposed to hold is named
+    MissingField(&'static str),
+    /// A field was found but its text couldn't be parsed, the field and the
+    /// raw text are both kept around
+    Parse { field: &'static str, text: String },
+    /// The client's [`ResponseCache`](crate::ResponseCache) is in offline mode and doesn't
+    /// have the page
+    Offline,
+    /// A downloaded module didn't hash to the MD5 scraped from its page, both digests are kept
+    /// around
+    ChecksumMismatch {
+        expected: crate::Md5Digest,
+        actual: crate::Md5Digest,
+    },
+    /// Reading or writing a local file failed, or the writer a download was streamed into did
+    Io(String),
+    /// An argument was out of the range the site takes, so nothing was sent, the argument and
+    /// the value it had are both kept around
+    InvalidArgument { name: &'static str, value: String },
+}
+
+impl Error {
+    /// Whether trying the same request again might work, this is true for timeouts, connection
+    /// failures (resets, refusals and the like) and `5xx` statuses. It's what the default
+    /// [`RetryPolicy`](crate::RetryPolicy) goes by.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Timeout | Error::Connection(_) => true,
+            Error::Status(code) => (500..600).contains(code),
+            _ => false,
+        }
+    }
+
+    /// Reading a response body failed, a timeout stays retryable while anything else means
+    /// the body can't be used. Local I/O goes through the [`From`] impl and becomes
+    /// [`Error::Io`] instead.
+    pub(crate) fn body_read(err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => Error::Timeout,
+            _ => Error::Decode(err.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound => write!(f, "not found on mod archive"),
+            Error::Timeout => write!(f, "request timed out"),
+            Error::Connection(msg) => write!(f, "connection error: {}", msg),
+            Error::Transport(msg) => write!(f, "transport error: {}", msg),
+            Error::Status(code) => write!(f, "server returned http status {}", code),
+            Error::Decode(msg) => write!(f, "failed to decode response: {}", msg),
+            Error::MissingField(field) => write!(f, "field `{}` is missing from the page", field),
+            Error::Parse { field, text } => {
+                write!(f, "failed to parse field `{}` from {:?}", field, text)
+            }
+            Error::Offline => write!(f, "page isn't cached and the cache is in offline mode"),
+            Error::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "downloaded module has md5 {} but {} was expected",
+                actual, expected
+            ),
+            Error::Io(msg) => write!(f, "i/o error: {}", msg),
+            Error::InvalidArgument { name, value } => {
+                write!(f, "invalid value {:?} for `{}`", value, name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ureq::Error> for Error {
+    fn from(err: ureq::Error) -> Self {
+        match err {
+            ureq::Error::Status(code, _) => Error::Status(code),
+            ureq::Error::Transport(transport) => {
+                let timed_out = std::error::Error::source(&transport)
+                    .and_then(|source| source.downcast_ref::<std::io::Error>())
+                    .map(|io| {
+                        matches!(
+                            io.kind(),
+                            std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+                        )
+                    })
+                    .unwrap_or(false);
+
+                match transport.kind() {
+                    _ if timed_out => Error::Timeout,
+                    ureq::ErrorKind::Dns
+                    | ureq::ErrorKind::ConnectionFailed
+                    | ureq::ErrorKind::Io => Error::Connection(transport.to_string()),
+                    _ => Error::Transport(transport.to_string()),
+                }
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    #[test]
+    fn display_names_field_and_text() {
+        let err = Error::Parse {
+            field: "download_count",
+            text: "lots".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "failed to parse field `download_count` from \"lots\""
+        );
+    }
+
+    #[test]
+    fn io_errors() {
+        use std::io::ErrorKind;
+
+        // local failures aren't the server's fault and aren't worth retrying
+        let local: Error = std::io::Error::new(ErrorKind::TimedOut, "disk").into();
+        assert_eq!(local, Error::Io("disk".into()));
+        assert!(!local.is_retryable());
+
+        let body = Error::body_read(std::io::Error::new(ErrorKind::TimedOut, "slow"));
+        assert_eq!(body, Error::Timeout);
+        assert_eq!(
+            Error::body_read(std::io::Error::new(ErrorKind::InvalidData, "garbled")),
+            Error::Decode("garbled".into())
+        );
+    }
+}
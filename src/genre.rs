@@ -0,0 +1,258 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::search::Listing;
+use crate::{ModArchiveClient, ModInfo, SearchPage, SearchPages, Transport};
+
+/// Declares the genres along with their Mod Archive IDs and the names the site shows, so the
+/// three can't drift apart
+macro_rules! genres {
+    ($($variant:ident = $id:literal, $name:literal;)*) => {
+        /// A genre from Mod Archive's genre taxonomy, backed by the genre ID the site uses.
+        ///
+        /// [`ModInfo::genre_kind()`] gets the genre of a module, and
+        /// [`ModArchiveClient::genre_pages()`] walks through every module in one. The
+        /// [`Display`](fmt::Display) impl gives back the name the site uses, like
+        /// `Trance - Dream`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+        pub enum Genre {
+            $(
+                #[doc = $name]
+                $variant,
+            )*
+        }
+
+        impl Genre {
+            /// Every genre, in the order the site lists them
+            pub const ALL: &'static [Genre] = &[$(Genre::$variant),*];
+
+            /// The ID Mod Archive uses for the genre in its links and search form
+            pub fn id(self) -> u32 {
+                match self {
+                    $(Genre::$variant => $id,)*
+                }
+            }
+
+            /// The genre as Mod Archive writes it, `Trance - Dream`, `Chiptune` and so on
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Genre::$variant => $name,)*
+                }
+            }
+        }
+    };
+}
+
+genres! {
+    Alternative = 48, "Alternative";
+    Gothic = 38, "Gothic";
+    Grunge = 103, "Grunge";
+    MetalExtreme = 37, "Metal - Extreme";
+    Metal = 36, "Metal (general)";
+    Punk = 35, "Punk";
+    Chiptune = 54, "Chiptune";
+    DemoStyle = 55, "Demo Style";
+    OneHourCompo = 53, "One Hour Compo";
+    Chillout = 106, "Chillout";
+    Ambient = 2, "Electronic - Ambient";
+    Breakbeat = 9, "Electronic - Breakbeat";
+    Dance = 3, "Electronic - Dance";
+    DrumAndBass = 6, "Electronic - Drum & Bass";
+    Gabber = 40, "Electronic - Gabber";
+    Hardcore = 39, "Electronic - Hardcore";
+    House = 10, "Electronic - House";
+    Idm = 99, "Electronic - IDM";
+    Industrial = 34, "Electronic - Industrial";
+    Jungle = 60, "Electronic - Jungle";
+    Minimal = 101, "Electronic - Minimal";
+    ElectronicOther = 100, "Electronic - Other";
+    ElectronicProgressive = 11, "Electronic - Progressive";
+    Rave = 65, "Electronic - Rave";
+    Techno = 7, "Electronic - Techno";
+    Electronic = 1, "Electronic (general)";
+    TranceAcid = 63, "Trance - Acid";
+    TranceDream = 67, "Trance - Dream";
+    TranceGoa = 66, "Trance - Goa";
+    TranceHard = 64, "Trance - Hard";
+    TranceProgressive = 85, "Trance - Progressive";
+    TranceTribal = 70, "Trance - Tribal";
+    Trance = 71, "Trance (general)";
+    BigBand = 74, "Big Band";
+    Blues = 19, "Blues";
+    JazzAcid = 30, "Jazz - Acid";
+    JazzModern = 31, "Jazz - Modern";
+    Jazz = 29, "Jazz (general)";
+    Swing = 75, "Swing";
+    Bluegrass = 105, "Bluegrass";
+    Classical = 20, "Classical";
+    Comedy = 45, "Comedy";
+    Country = 18, "Country";
+    Experimental = 46, "Experimental";
+    Fantasy = 52, "Fantasy";
+    Folk = 21, "Folk";
+    Fusion = 102, "Fusion";
+    Medieval = 28, "Medieval";
+    NewAge = 44, "New Ages";
+    Orchestral = 50, "Orchestral";
+    Other = 41, "Other";
+    Piano = 59, "Piano";
+    Religious = 49, "Religious";
+    Soundtrack = 43, "Soundtrack";
+    Spiritual = 47, "Spiritual";
+    VideoGame = 8, "Video Game";
+    VocalMontage = 76, "Vocal Montage";
+    World = 42, "World";
+    Ballad = 56, "Ballad";
+    Disco = 58, "Disco";
+    EasyListening = 107, "Easy Listening";
+    Funk = 32, "Funk";
+    PopSoft = 62, "Pop - Soft";
+    PopSynth = 61, "Pop - Synth";
+    Pop = 12, "Pop (general)";
+    RockHard = 14, "Rock - Hard";
+    RockSoft = 15, "Rock - Soft";
+    Rock = 13, "Rock (general)";
+    Soul = 33, "Soul";
+    Reggae = 27, "Reggae";
+    HipHop = 22, "Hip-Hop";
+    RnB = 26, "R&B";
+    Ska = 24, "Ska";
+    Latin = 25, "Latin";
+}
+
+impl Genre {
+    /// The genre with the given Mod Archive ID, if there is one
+    pub fn from_id(id: u32) -> Option<Genre> {
+        Genre::ALL.iter().copied().find(|genre| genre.id() == id)
+    }
+}
+
+impl FromStr for Genre {
+    type Err = crate::Error;
+
+    /// Takes the name as the site shows it, case insensitive
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Genre::ALL
+            .iter()
+            .copied()
+            .find(|genre| genre.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| crate::Error::Parse {
+                field: "genre",
+                text: s.into(),
+            })
+    }
+}
+
+impl TryFrom<String> for Genre {
+    type Error = crate::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Genre> for String {
+    fn from(genre: Genre) -> Self {
+        genre.name().into()
+    }
+}
+
+impl From<Genre> for u32 {
+    fn from(genre: Genre) -> Self {
+        genre.id()
+    }
+}
+
+impl fmt::Display for Genre {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl ModInfo {
+    /// The [`ModInfo::genre`] text as a [`Genre`], `None` for modules without one (the site
+    /// shows `n/a`) and genres the crate doesn't know about
+    pub fn genre_kind(&self) -> Option<Genre> {
+        self.genre.parse().ok()
+    }
+
+    /// Returns the given page (starting from 1) of the modules in `genre`.
+    ///
+    /// This goes through a default [`ModArchiveClient`], use
+    /// [`ModArchiveClient::genre_pages()`] to walk through all of them.
+    pub fn genre_page(genre: Genre, page: u32) -> Result<SearchPage, crate::Error> {
+        ModArchiveClient::new().genre_page(genre, page)
+    }
+}
+
+impl<T: Transport> ModArchiveClient<T> {
+    /// Same as [`ModInfo::genre_page()`] but through this client's transport
+    pub fn genre_page(&self, genre: Genre, page: u32) -> Result<SearchPage, crate::Error> {
+        self.listing_page(&Listing::Genre(genre), page)
+    }
+
+    /// Returns an iterator that walks through every page of the modules in `genre`
+    pub fn genre_pages(&self, genre: Genre) -> SearchPages<'_, T> {
+        SearchPages::new(self, Listing::Genre(genre), 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{fixture_client, fixture_page, page_of};
+    use crate::{Error, Genre, ModInfo};
+
+    /// Answers two pages of Trance - Dream modules, anything else isn't there
+    fn genre_site(url: &str) -> Option<String> {
+        url.contains("query=67&search_type=genre").then(|| {
+            fixture_page(
+                include_str!("../tests/fixtures/browse_genre.html"),
+                page_of(url),
+                &[(61772, 61800), (61790, 61801)],
+            )
+        })
+    }
+
+    #[test]
+    fn genre_names_and_ids() {
+        for genre in Genre::ALL {
+            assert_eq!(genre.name().parse::<Genre>().unwrap(), *genre);
+            assert_eq!(Genre::from_id(genre.id()), Some(*genre));
+        }
+        assert_eq!(
+            "electronic - drum & bass".parse::<Genre>().unwrap(),
+            Genre::DrumAndBass
+        );
+        assert!("n/a".parse::<Genre>().is_err());
+        assert_eq!(Genre::from_id(0), None);
+
+        let modinfo =
+            ModInfo::from_html(61772, include_str!("../tests/fixtures/module.html")).unwrap();
+        assert_eq!(modinfo.genre_kind(), Some(Genre::TranceDream));
+    }
+
+    #[test]
+    fn browses_a_genre() {
+        let client = fixture_client(genre_site);
+
+        let page = client.genre_page(Genre::TranceDream, 1).unwrap();
+        assert_eq!((page.page, page.page_count, page.result_count), (1, 2, 4));
+        let ids: Vec<u32> = page.results.iter().map(|result| result.id).collect();
+        assert_eq!(ids, [61772, 61790]);
+
+        let ids: Vec<u32> = client
+            .genre_pages(Genre::TranceDream)
+            .flat_map(|page| page.unwrap().results)
+            .map(|result| result.id)
+            .collect();
+        assert_eq!(ids, [61772, 61790, 61800, 61801]);
+
+        assert_eq!(
+            client.genre_page(Genre::Chiptune, 1).unwrap_err(),
+            Error::Status(404)
+        );
+    }
+}
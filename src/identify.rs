@@ -0,0 +1,215 @@
+use std::path::{Path, PathBuf};
+
+use crate::{Md5Digest, ModArchiveClient, ModInfo, SearchQuery, SearchType, Transport};
+
+/// One file looked up by [`ModArchiveClient::identify_dir()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentifiedFile {
+    /// Where the file is
+    pub path: PathBuf,
+    /// The module the file turned out to be, or why it couldn't be identified
+    pub modinfo: Result<ModInfo, crate::Error>,
+}
+
+impl<T: Transport> ModArchiveClient<T> {
+    /// Finds the module whose file hashes to `md5` with a hash search, every result is checked
+    /// against the MD5 on its module page. A result whose page fails to load is skipped, when
+    /// nothing matches that's an [`Error::NotFound`](crate::Error::NotFound) or the last error
+    /// if a page failed (it might have been the match).
+    pub fn identify(&self, md5: &Md5Digest) -> Result<ModInfo, crate::Error> {
+        let query = SearchQuery::new(md5.to_string()).search_type(SearchType::Hash);
+        let mut last_err = None;
+
+        for result in self.search(&query)?.results {
+            match self.get_mod(result.id) {
+                Ok(modinfo) if modinfo.md5 == *md5 => return Ok(modinfo),
+                Ok(_) => {}
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        Err(last_err.unwrap_or(crate::Error::NotFound))
+    }
+
+    /// Hashes the file at `path` and looks it up with [`identify()`](ModArchiveClient::identify),
+    /// the filename doesn't matter at all so this works on renamed files too
+    pub fn identify_file(&self, path: impl AsRef<Path>) -> Result<ModInfo, crate::Error> {
+        self.identify(&hash_file(path.as_ref())?)
+    }
+
+    /// Identifies every file under `dir` (subdirectories included), each one gets its own
+    /// result so one unknown file doesn't stop the rest. The paths come back sorted.
+    /// Symlinked files are identified but symlinked directories aren't followed, so a link
+    /// back up the tree can't send it around in circles.
+    ///
+    /// Every file takes at least two requests so a big collection takes a while with the
+    /// default [`RateLimiter`](crate::RateLimiter).
+    ///
+    /// ```rust
+    /// use trackermeta::ModArchiveClient;
+    ///
+    /// let client = ModArchiveClient::new();
+    /// for file in client.identify_dir("modules").unwrap() {
+    ///     match file.modinfo {
+    ///         Ok(modinfo) => println!("{}: {} (#{})", file.path.display(), modinfo.title, modinfo.id),
+    ///         Err(err) => println!("{}: {}", file.path.display(), err),
+    ///     }
+    /// }
+    /// ```
+    pub fn identify_dir(&self, dir: impl AsRef<Path>) -> Result<Vec<IdentifiedFile>, crate::Error> {
+        let mut files = Vec::new();
+        collect_files(dir.as_ref(), &mut files)?;
+        files.sort();
+
+        Ok(files
+            .into_iter()
+            .map(|path| IdentifiedFile {
+                modinfo: self.identify_file(&path),
+                path,
+            })
+            .collect())
+    }
+}
+
+impl ModInfo {
+    /// Finds the module a local file is, going by its MD5 hash, see
+    /// [`ModArchiveClient::identify_file()`]
+    pub fn identify_file(path: impl AsRef<Path>) -> Result<ModInfo, crate::Error> {
+        ModArchiveClient::new().identify_file(path)
+    }
+
+    /// Identifies every file in a directory, see [`ModArchiveClient::identify_dir()`]
+    pub fn identify_dir(dir: impl AsRef<Path>) -> Result<Vec<IdentifiedFile>, crate::Error> {
+        ModArchiveClient::new().identify_dir(dir)
+    }
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> crate::Error {
+    crate::Error::Io(format!("{}: {}", path.display(), err))
+}
+
+fn hash_file(path: &Path) -> Result<Md5Digest, crate::Error> {
+    let file = std::fs::File::open(path).map_err(|err| io_error(path, err))?;
+    Md5Digest::from_reader(std::io::BufReader::new(file)).map_err(|err| io_error(path, err))
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), crate::Error> {
+    for entry in std::fs::read_dir(dir).map_err(|err| io_error(dir, err))? {
+        let entry = entry.map_err(|err| io_error(dir, err))?;
+        let path = entry.path();
+        // file_type() doesn't follow symlinks, only the ones pointing at files are taken
+        let file_type = entry.file_type().map_err(|err| io_error(&path, err))?;
+        if file_type.is_dir() {
+            collect_files(&path, files)?;
+        } else if file_type.is_file() || (file_type.is_symlink() && path.is_file()) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::fixture_client;
+    use crate::{Error, Md5Digest};
+
+    const KNOWN_FILE: &[u8] = b"Extended Module: 7th Dance";
+
+    /// Serves hash searches and module pages, every module page carries the MD5 of `KNOWN_FILE`
+    fn hash_site(url: &str) -> Option<String> {
+        let url = url::Url::parse(url).unwrap();
+        let param = |key: &str| {
+            url.query_pairs()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.into_owned())
+        };
+        let known = Md5Digest::compute(KNOWN_FILE).to_string();
+
+        Some(if param("request").as_deref() == Some("search") {
+            assert_eq!(param("search_type").as_deref(), Some("hash"));
+            if param("query") == Some(known) {
+                include_str!("../tests/fixtures/search.html")
+            } else {
+                include_str!("../tests/fixtures/search_empty.html")
+            }
+            .to_string()
+        } else {
+            include_str!("../tests/fixtures/module.html")
+                .replace("9a0364b9e99bb480dd25e1f0284c8555", &known)
+        })
+    }
+
+    #[test]
+    fn identifies_by_hash() {
+        let client = fixture_client(hash_site);
+        let dir = std::env::temp_dir().join(format!("trackermeta-identify-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("nested").join("renamed.xm"), KNOWN_FILE).unwrap();
+        std::fs::write(dir.join("unknown.mod"), b"not on mod archive").unwrap();
+
+        let modinfo = client
+            .identify_file(dir.join("nested").join("renamed.xm"))
+            .unwrap();
+        assert_eq!(modinfo.id, 88676);
+        assert_eq!(modinfo.md5, Md5Digest::compute(KNOWN_FILE));
+
+        let results = client.identify_dir(&dir).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].path, dir.join("nested").join("renamed.xm"));
+        assert_eq!(results[0].modinfo.as_ref().unwrap().id, 88676);
+        assert_eq!(results[1].modinfo, Err(Error::NotFound));
+
+        assert!(matches!(
+            client.identify_file(dir.join("missing.it")),
+            Err(Error::Io(_))
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn failing_results_are_skipped() {
+        let known = Md5Digest::compute(KNOWN_FILE);
+
+        // the first result is gone and the second one is a different module
+        let client = fixture_client(|url| {
+            if url.ends_with("query=88676") {
+                None
+            } else if url.ends_with("query=88677") {
+                Some(include_str!("../tests/fixtures/module.html").to_string())
+            } else {
+                hash_site(url)
+            }
+        });
+        assert_eq!(client.identify(&known).unwrap().id, 170021);
+
+        // with nothing matching the error isn't swallowed
+        let client = fixture_client(|url| {
+            (!url.contains("request=view_by_moduleid")).then(|| hash_site(url).unwrap())
+        });
+        assert_eq!(client.identify(&known).unwrap_err(), Error::Status(404));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_cycles_are_not_followed() {
+        let client = fixture_client(hash_site);
+        let dir = std::env::temp_dir().join(format!("trackermeta-symlinks-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("nested").join("renamed.xm"), KNOWN_FILE).unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("nested").join("parent")).unwrap();
+        std::os::unix::fs::symlink(dir.join("nested").join("renamed.xm"), dir.join("linked.xm"))
+            .unwrap();
+
+        let results = client.identify_dir(&dir).unwrap();
+        let paths: Vec<_> = results.iter().map(|file| file.path.clone()).collect();
+        assert_eq!(
+            paths,
+            [dir.join("linked.xm"), dir.join("nested").join("renamed.xm")]
+        );
+        assert!(results.iter().all(|file| file.modinfo.is_ok()));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
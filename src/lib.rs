@@ -34,29 +34,58 @@
 //! [Mod Archive]: https://modarchive.org
 #![allow(clippy::needless_doctest_main)]
 
-use chrono::prelude::{DateTime, Utc};
-
-// https://stackoverflow.com/a/64148190
-fn iso8601_time(st: &std::time::SystemTime) -> String {
-    let dt: DateTime<Utc> = (*st).into();
-    format!("{}", dt.format("%+"))
-}
+mod artist;
+mod browse;
+mod builder;
+mod cache;
+mod client;
+mod comment;
+mod error;
+mod genre;
+mod identify;
+pub mod local;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+mod parse;
+mod random;
+mod ratelimit;
+mod retry;
+mod review;
+mod search;
+#[cfg(test)]
+mod test_support;
+mod transport;
+mod types;
+
+pub use artist::Artist;
+pub use builder::ClientBuilder;
+pub use cache::ResponseCache;
+pub use client::ModArchiveClient;
+pub use comment::Comment;
+pub use error::Error;
+pub use genre::Genre;
+pub use identify::IdentifiedFile;
+pub use random::RandomFilter;
+pub use ratelimit::RateLimiter;
+pub use retry::RetryPolicy;
+pub use review::Review;
+pub use search::{ModInfos, SearchPage, SearchPages, SearchQuery, SearchType};
+pub use transport::{Response, Transport, UreqTransport};
+pub use types::{License, Md5Digest, Member, ModuleFormat, Rating};
 
-/// Error enum for functions in the crate that return a [`Result`]
-#[derive(Debug)]
-pub enum Error {
-    NotFound,
-}
+use chrono::prelude::{DateTime, Utc};
 
 /// Simple struct to represent a search result, id and filename will be provided in each
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModSearch {
     pub id: u32,
     pub filename: String,
 }
 
 /// Struct containing all of the info about a module
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModInfo {
     /// The module ID of the module on Mod Archive
     pub id: u32,
@@ -64,15 +93,16 @@ pub struct ModInfo {
     pub filename: String,
     /// The title of the module
     pub title: String,
-    /// The file size of the module, use the
-    /// crate `byte-unit` to convert them to other units
-    pub size: String,
-    /// The MD5 hash of the module file as a string
-    pub md5: String,
+    /// The uncompressed file size of the module in bytes
+    pub size: u64,
+    /// The file size as the site shows it, for example `204.54 KB`
+    pub size_text: String,
+    /// The MD5 hash of the module file
+    pub md5: Md5Digest,
     /// The format of the module, for example `XM`, `IT`
     /// or `MOD` and more, basically the extension of the
     /// module file
-    pub format: String,
+    pub format: ModuleFormat,
     /// Spotlit module or not
     pub spotlit: bool,
     /// Download count of the module at the time of scraping
@@ -80,230 +110,54 @@ pub struct ModInfo {
     /// Times the module has been favourited at the time of scraping
     pub fav_count: u32,
     /// The time when it was scraped
-    pub scrape_time: String,
+    pub scrape_time: DateTime<Utc>,
     /// The channel count of the module
     pub channel_count: u32,
-    /// The genre of the module
+    /// The genre of the module as the site shows it, [`ModInfo::genre_kind()`] gives it as a
+    /// [`Genre`]
     pub genre: String,
-    /// The upload date of the module
-    pub upload_date: String,
+    /// The upload date of the module, the site only gives the day so this is always midnight
+    pub upload_date: DateTime<Utc>,
+    /// The upload date as the site shows it, for example `Thu 7th Dec 2000`
+    pub upload_date_text: String,
     /// The instrument text of the module
     pub instrument_text: String,
-}
-
-cfg_if::cfg_if! {
-    if #[cfg(feature = "infinity-retry")] {
-        fn inner_request(mod_id: u32) -> String{
-            loop {
-                match ureq::get(
-                    format!(
-                        "https://modarchive.org/index.php?request=view_by_moduleid&query={}",
-                        mod_id
-                    )
-                    .as_str(),
-                )
-                .timeout(std::time::Duration::from_secs(60))
-                .call() {
-                    Ok(req) => {
-                        return req.into_string().unwrap()
-                    }
-                    Err(_) => continue,
-                };
-            }
-        }
-    } else {
-        fn inner_request(mod_id: u32) -> String {
-            let body = ureq::get(
-                format!(
-                    "https://modarchive.org/index.php?request=view_by_moduleid&query={}",
-                    mod_id
-                )
-                .as_str(),
-            )
-            .timeout(std::time::Duration::from_secs(60))
-            .call()
-            .unwrap()
-            .into_string()
-            .unwrap();
-
-            body
-        }
-    }
+    /// The artists the module is credited to, empty when the site doesn't know who made it
+    pub artists: Vec<Member>,
+    /// The average rating members gave the module, `None` if nobody rated it yet
+    pub member_rating: Option<Rating>,
+    /// How many members rated the module
+    pub member_votes: u32,
+    /// The score the module got from a Mod Archive reviewer, if it was reviewed
+    pub reviewer_rating: Option<Rating>,
+    /// The license the module was uploaded under, `None` when the page doesn't show one so
+    /// treat that as no permission at all
+    pub license: Option<License>,
 }
 
 impl ModInfo {
     /// Probably the singular most important function in this crate, takes a module ID (can be
     /// generated at random, deliberately entered or acquired by resolving a filename and
     /// picking a search result), and then gives you a full [`ModInfo`] struct.
+    ///
+    /// This goes through a default [`ModArchiveClient`], use [`ModArchiveClient::get_mod()`]
+    /// if you want to bring your own [`Transport`].
     pub fn get(mod_id: u32) -> Result<ModInfo, crate::Error> {
-        let body = inner_request(mod_id);
-
-        let dom = tl::parse(body.as_ref(), tl::ParserOptions::default()).unwrap();
-        let parser = dom.parser();
-
-        let id = mod_id;
-        let scrape_time = iso8601_time(&std::time::SystemTime::now());
-
-        let valid = dom.get_elements_by_class_name("mod-page-archive-info")
-                .next()
-                .is_some();
+        ModArchiveClient::new().get_mod(mod_id)
+    }
 
-        if !valid {
-            return Err(crate::Error::NotFound);
-        }
+    /// Runs a full search built with [`SearchQuery`], which can search by more than the
+    /// filename and filter by format, genre, channel count and size. Returns the page of results
+    /// the query asks for.
+    pub fn search(query: &SearchQuery) -> Result<SearchPage, crate::Error> {
+        ModArchiveClient::new().search(query)
+    }
 
-        let filename = {
-            dom.get_elements_by_class_name("module-sub-header")
-                .next()
-                .unwrap() // we can unwrap because if its absent we've already errored up above
-                .get(parser)
-                .unwrap()
-                .inner_text(parser)
-                .replace(['(', ')'], "")
-        };
-
-        let title = {
-            escaper::decode_html(
-                &dom.query_selector("h1")
-                    .and_then(|mut iter| iter.next())
-                    .unwrap()
-                    .get(parser)
-                    .unwrap()
-                    .inner_text(parser)
-                    .replace(&format!(" ({})", &filename), ""),
-            )
-            .unwrap()
-        };
-
-        let size = {
-            dom.query_selector("li.stats")
-                // get the 8th hit (nth starts from 0)
-                .and_then(|mut iter| iter.nth(7))
-                .unwrap()
-                .get(parser)
-                .unwrap()
-                .inner_text(parser)
-                .replace("Uncompressed Size: ", "")
-        };
-
-        let md5 = {
-            dom.query_selector("li.stats")
-                .and_then(|mut iter| iter.nth(4))
-                .unwrap()
-                .get(parser)
-                .unwrap()
-                .inner_text(parser)
-                .replace("MD5: ", "")
-        };
-
-        let format = {
-            dom.query_selector("li.stats")
-                .and_then(|mut iter| iter.nth(5))
-                .unwrap()
-                .get(parser)
-                .unwrap()
-                .inner_text(parser)
-                .replace("Format: ", "")
-        };
-
-        let spotlit = dom.get_elements_by_class_name("mod-page-featured")
-                .next()
-                .is_some();
-
-        let download_count = {
-            dom.query_selector("li.stats")
-                .and_then(|mut iter| iter.nth(2))
-                .unwrap()
-                .get(parser)
-                .unwrap()
-                .inner_text(parser)
-                .replace("Downloads: ", "")
-                .parse()
-                .unwrap()
-        };
-
-        let fav_count = {
-            dom.query_selector("li.stats")
-                .and_then(|mut iter| iter.nth(3))
-                .unwrap()
-                .get(parser)
-                .unwrap()
-                .inner_text(parser)
-                .replace("Favourited: ", "")
-                .replace(" times", "")
-                .parse()
-                .unwrap()
-        };
-
-        let channel_count = {
-            dom.query_selector("li.stats")
-                .and_then(|mut iter| iter.nth(6))
-                .unwrap()
-                .get(parser)
-                .unwrap()
-                .inner_text(parser)
-                .replace("Channels: ", "")
-                .parse()
-                .unwrap()
-        };
-
-        dom.query_selector("a.master");
-
-        let genre = {
-            dom.query_selector("li.stats")
-                .and_then(|mut iter| iter.nth(8))
-                .unwrap()
-                .get(parser)
-                .unwrap()
-                .inner_text(parser)
-                .replace("Genre: ", "")
-        };
-
-        let upload_date = {
-            dom.query_selector("li.stats")
-                .and_then(|mut iter| iter.next())
-                .unwrap()
-                .get(parser)
-                .unwrap()
-                .inner_text(parser)
-                .split(" times since ")
-                .nth(1)
-                .unwrap()
-                .replace(" :D", "")
-                .trim()
-                .into()
-        };
-
-        let instrument_text = {
-            escaper::decode_html(
-                &dom.query_selector("pre")
-                    .and_then(|mut iter| iter.nth(1))
-                    .unwrap()
-                    .get(parser)
-                    .unwrap()
-                    .inner_text(parser),
-            )
-            .unwrap()
-            .trim()
-            .into()
-        };
-
-        Ok(ModInfo {
-            id,
-            filename,
-            title,
-            size,
-            md5,
-            format,
-            spotlit,
-            download_count,
-            fav_count,
-            scrape_time,
-            channel_count,
-            genre,
-            upload_date,
-            instrument_text,
-        })
+    /// Does the same extraction as [`ModInfo::get()`] but on a module page you already have,
+    /// nothing is fetched. `mod_id` should be the ID the page belongs to, it's just copied over
+    /// into the [`ModInfo`].
+    pub fn from_html(mod_id: u32, html: &str) -> Result<ModInfo, crate::Error> {
+        parse::mod_info(mod_id, html)
     }
 
     /// Returns a Mod Archive download link for the given module, you can get this struct by using
@@ -317,62 +171,63 @@ impl ModInfo {
         )
     }
 
+    /// Downloads the module file and checks it against [`ModInfo::md5`], a file that doesn't
+    /// match is an [`Error::ChecksumMismatch`].
+    ///
+    /// This goes through a default [`ModArchiveClient`], use [`ModArchiveClient::download()`]
+    /// if you want to bring your own [`Transport`].
+    pub fn download(&self) -> Result<Vec<u8>, crate::Error> {
+        ModArchiveClient::new().download(self)
+    }
+
+    /// Like [`ModInfo::download()`] but streams the file into `writer`, see
+    /// [`ModArchiveClient::download_to()`]
+    pub fn download_to(&self, writer: impl std::io::Write) -> Result<u64, crate::Error> {
+        ModArchiveClient::new().download_to(self, writer)
+    }
+
+    /// Like [`ModInfo::download()`] but saves the file to `path`, see
+    /// [`ModArchiveClient::download_to_path()`]
+    pub fn download_to_path(&self, path: impl AsRef<std::path::Path>) -> Result<u64, crate::Error> {
+        ModArchiveClient::new().download_to_path(self, path)
+    }
+
     /// Searches for your string on Mod Archive and returns the results on the first page (a.k.a
     /// only up to the first 40) as a vector of [`ModSearch`]
+    ///
+    /// This goes through a default [`ModArchiveClient`], use
+    /// [`ModArchiveClient::resolve_filename()`] if you want to bring your own [`Transport`].
     pub fn resolve_filename(filename: &str) -> Result<Vec<ModSearch>, crate::Error> {
-        let body: String = ureq::get(
-                format!(
-                    "https://modarchive.org/index.php?request=search&query={}&submit=Find&search_type=filename",
-                    filename
-                )
-                .as_str(),
-            )
-            .call()
-            .unwrap()
-            .into_string()
-            .unwrap();
-
-        let dom = tl::parse(&body, tl::ParserOptions::default()).unwrap();
-        let parser = dom.parser();
-
-        let status = dom.query_selector("h1.site-wide-page-head-title");
-
-        match status {
-            Some(_) => {}
-            None => return Err(crate::Error::NotFound),
-        };
-        // from this point on we can unwrap after each query selector
-        // because our info will for sure be present.
-
-        let links: Vec<ModSearch> = dom
-            .query_selector("a.standard-link[title]")
-            .unwrap()
-            .map(|nodehandle| {
-                let node = nodehandle.get(parser).unwrap();
-
-                let id = match node.as_tag().unwrap().attributes().get("href") {
-                    Some(Some(a)) => a
-                        .as_utf8_str()
-                        .split("query=")
-                        .nth(1)
-                        .unwrap()
-                        .parse()
-                        .unwrap(),
-                    Some(None) => unreachable!(),
-                    None => unreachable!(),
-                };
-
-                let filename = node.inner_text(parser).into();
-
-                ModSearch { id, filename }
-            })
-            .collect();
+        ModArchiveClient::new().resolve_filename(filename)
+    }
+
+    /// Like [`ModInfo::resolve_filename()`] but returns the given page of results (starting
+    /// from 1) along with the total page and result counts
+    pub fn resolve_filename_page(filename: &str, page: u32) -> Result<SearchPage, crate::Error> {
+        ModArchiveClient::new().resolve_filename_page(filename, page)
+    }
+
+    /// Like [`ModInfo::resolve_filename()`] but follows every page of results and returns all
+    /// of them, this does one request per page so be gentle with it.
+    pub fn resolve_filename_all(filename: &str) -> Result<Vec<ModSearch>, crate::Error> {
+        let client = ModArchiveClient::new();
+        let mut results = Vec::new();
+
+        for page in client.resolve_filename_pages(filename) {
+            results.extend(page?.results);
+        }
 
-        Ok(links)
+        Ok(results)
     }
 }
 
 impl ModSearch {
+    /// Does the same extraction as [`ModInfo::resolve_filename()`] but on a search results page
+    /// you already have, nothing is fetched.
+    pub fn from_html(html: &str) -> Result<Vec<ModSearch>, crate::Error> {
+        Ok(parse::search_page(html)?.results)
+    }
+
     /// Get the download link of this specific module.
     pub fn get_download_link(&self) -> String {
         format!(
@@ -380,13 +235,211 @@ impl ModSearch {
             self.id, self.filename
         )
     }
+
+    /// Downloads the module file, search results don't carry an MD5 so this gets the full
+    /// [`ModInfo`] first (one more request) and checks the file against it, see
+    /// [`ModInfo::download()`]
+    pub fn download(&self) -> Result<Vec<u8>, crate::Error> {
+        let client = ModArchiveClient::new();
+        client.download(&client.get_mod(self.id)?)
+    }
+
+    /// Like [`ModSearch::download()`] but streams the file into `writer`
+    pub fn download_to(&self, writer: impl std::io::Write) -> Result<u64, crate::Error> {
+        let client = ModArchiveClient::new();
+        client.download_to(&client.get_mod(self.id)?, writer)
+    }
+
+    /// Like [`ModSearch::download()`] but saves the file to `path`
+    pub fn download_to_path(&self, path: impl AsRef<std::path::Path>) -> Result<u64, crate::Error> {
+        let client = ModArchiveClient::new();
+        client.download_to_path(&client.get_mod(self.id)?, path)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::ModInfo;
+    use crate::{Error, License, Member, ModInfo, ModSearch, ModuleFormat, Rating};
+    use chrono::prelude::{TimeZone, Utc};
+
+    const MODULE: &str = include_str!("../tests/fixtures/module.html");
+    const MODULE_SPOTLIT: &str = include_str!("../tests/fixtures/module_spotlit.html");
+    const MODULE_NOT_FOUND: &str = include_str!("../tests/fixtures/module_not_found.html");
+    const MODULE_UNUSUAL_GENRE: &str = include_str!("../tests/fixtures/module_unusual_genre.html");
+    const MODULE_EMPTY_INSTR_TEXT: &str =
+        include_str!("../tests/fixtures/module_empty_instrument_text.html");
+    const SEARCH: &str = include_str!("../tests/fixtures/search.html");
+    const SEARCH_EMPTY: &str = include_str!("../tests/fixtures/search_empty.html");
+
+    #[test]
+    fn from_html_not_a_module_page() {
+        let invalid = ModInfo::from_html(1, "<html><body><h1>Oops</h1></body></html>");
+        assert_eq!(invalid.unwrap_err(), Error::NotFound);
+        assert_eq!(
+            ModSearch::from_html("<html></html>").unwrap_err(),
+            Error::NotFound
+        );
+    }
+
+    #[test]
+    fn fixture_module() {
+        let modinfo = ModInfo::from_html(61772, MODULE).unwrap();
+        assert_eq!(modinfo.id, 61772);
+        assert_eq!(modinfo.filename, "7th_dance.xm");
+        assert_eq!(modinfo.title, "7th Dance");
+        assert_eq!(modinfo.size, 209449);
+        assert_eq!(modinfo.size_text, "204.54 KB");
+        assert_eq!(modinfo.md5.to_string(), "9a0364b9e99bb480dd25e1f0284c8555");
+        assert_eq!(modinfo.format, ModuleFormat::Xm);
+        assert!(!modinfo.spotlit);
+        assert_eq!(modinfo.download_count, 2471);
+        assert_eq!(modinfo.fav_count, 12);
+        assert_eq!(modinfo.channel_count, 16);
+        assert_eq!(modinfo.genre, "Trance - Dream");
+        assert_eq!(
+            modinfo.upload_date,
+            Utc.with_ymd_and_hms(2000, 12, 7, 0, 0, 0).unwrap()
+        );
+        assert_eq!(modinfo.upload_date_text, "Thu 7th Dec 2000");
+        assert_eq!(
+            modinfo.instrument_text,
+            "7th  Dance
+
+             By:
+ Jari Ylamaki aka Yrde
+  27.11.2000 HELSINKI
+
+            Finland
+           SITE :
+  www.mp3.com/Yrde"
+        );
+        assert_eq!(
+            modinfo.artists,
+            [
+                Member {
+                    id: 69141,
+                    name: "Yrde".into()
+                },
+                Member {
+                    id: 70022,
+                    name: "Jari & Friends".into()
+                },
+            ]
+        );
+        assert_eq!(modinfo.member_rating, Some(Rating::from_hundredths(750)));
+        assert_eq!(modinfo.member_votes, 12);
+        assert_eq!(modinfo.reviewer_rating, Some(Rating::from_hundredths(800)));
+        assert_eq!(
+            modinfo.license,
+            Some(License::CcByNcSa {
+                url: "https://creativecommons.org/licenses/by-nc-sa/3.0/".into()
+            })
+        );
+    }
+
+    #[test]
+    fn fixture_spotlit() {
+        let modinfo = ModInfo::from_html(158263, MODULE_SPOTLIT).unwrap();
+        assert!(modinfo.spotlit);
+        assert_eq!(modinfo.title, "Beyond the Network & Back");
+        assert_eq!(modinfo.channel_count, 32);
+        assert!(modinfo.artists.is_empty());
+        assert_eq!(modinfo.member_rating, Some(Rating::from_hundredths(910)));
+        assert_eq!(modinfo.member_votes, 31);
+        assert_eq!(modinfo.reviewer_rating, None);
+        assert_eq!(modinfo.license, Some(License::PublicDomain));
+    }
+
+    #[test]
+    fn fixture_not_found() {
+        let invalid = ModInfo::from_html(30638, MODULE_NOT_FOUND);
+        assert_eq!(invalid.unwrap_err(), Error::NotFound);
+    }
+
+    #[test]
+    fn fixture_unusual_genre() {
+        let modinfo = ModInfo::from_html(99356, MODULE_UNUSUAL_GENRE).unwrap();
+        assert_eq!(modinfo.genre, "Electronic - Drum & Bass");
+        assert_eq!(modinfo.fav_count, 0);
+        assert_eq!(modinfo.instrument_text, "jungle <3");
+        assert_eq!(modinfo.member_rating, None);
+        assert_eq!(modinfo.member_votes, 0);
+    }
 
     #[test]
+    fn fixture_empty_instr_text() {
+        let modinfo = ModInfo::from_html(41070, MODULE_EMPTY_INSTR_TEXT).unwrap();
+        assert_eq!(modinfo.instrument_text, "");
+        assert_eq!(modinfo.genre, "n/a");
+        assert_eq!(modinfo.license, None);
+        assert_eq!(
+            modinfo.get_download_link().as_str(),
+            "https://api.modarchive.org/downloads.php?moduleid=41070#fading_horizont.mod"
+        );
+    }
+
+    #[test]
+    fn fixture_missing_stat() {
+        let broken = MODULE.replace("<li class=\"stats\">Genre: Trance - Dream</li>", "");
+        assert_eq!(
+            ModInfo::from_html(61772, &broken).unwrap_err(),
+            Error::MissingField("genre")
+        );
+
+        let broken = MODULE.replace("Downloads: 2471", "Downloads: lots");
+        assert_eq!(
+            ModInfo::from_html(61772, &broken).unwrap_err(),
+            Error::Parse {
+                field: "download_count",
+                text: "lots".into()
+            }
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let modinfo = ModInfo::from_html(61772, MODULE).unwrap();
+        let json = serde_json::to_string(&modinfo).unwrap();
+        assert!(json.contains("\"md5\":\"9a0364b9e99bb480dd25e1f0284c8555\""));
+        assert!(json.contains("\"format\":\"XM\""));
+        assert_eq!(serde_json::from_str::<ModInfo>(&json).unwrap(), modinfo);
+
+        let results = ModSearch::from_html(SEARCH).unwrap();
+        let json = serde_json::to_string(&results).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<ModSearch>>(&json).unwrap(),
+            results
+        );
+    }
+
+    #[test]
+    fn fixture_search() {
+        let results = ModSearch::from_html(SEARCH).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].id, 88676);
+        assert_eq!(results[2].filename, "virtual_monotone.xm");
+        assert_eq!(
+            results[0].get_download_link().as_str(),
+            "https://api.modarchive.org/downloads.php?moduleid=88676#virtual-monotone.mod"
+        );
+    }
+
+    #[test]
+    fn fixture_search_empty() {
+        assert!(ModSearch::from_html(SEARCH_EMPTY).unwrap().is_empty());
+    }
+}
+
+/// These hit modarchive.org so they're ignored by default, run them with
+/// `cargo test -- --ignored`. As the counters on the site change all the time they only check
+/// things that shouldn't.
+#[cfg(test)]
+mod live_tests {
+    use crate::{Artist, ModInfo};
+
+    #[test]
+    #[ignore = "hits modarchive.org"]
     fn instr_text() {
         let instr_text = ModInfo::get(61772).unwrap().instrument_text;
         assert_eq!(
@@ -404,24 +457,28 @@ mod tests {
     }
 
     #[test]
+    #[ignore = "hits modarchive.org"]
     fn invalid_modid() {
         let invalid = ModInfo::get(30638);
         assert!(invalid.is_err());
     }
 
     #[test]
+    #[ignore = "hits modarchive.org"]
     fn valid_modid() {
         let valid = ModInfo::get(99356);
         assert!(valid.is_ok());
     }
 
     #[test]
+    #[ignore = "hits modarchive.org"]
     fn spotlit_modid() {
         let module = ModInfo::get(158263).unwrap();
         assert!(module.spotlit);
     }
 
     #[test]
+    #[ignore = "hits modarchive.org"]
     fn name_resolving() {
         let mod_search = ModInfo::resolve_filename("virtual-monotone.mod");
         let mod_search = &mod_search.unwrap()[0];
@@ -433,6 +490,7 @@ mod tests {
     }
 
     #[test]
+    #[ignore = "hits modarchive.org"]
     fn dl_link_modinfo() {
         let modinfo = ModInfo::get(41070).unwrap();
         assert_eq!(
@@ -440,4 +498,51 @@ mod tests {
             "https://api.modarchive.org/downloads.php?moduleid=41070#fading_horizont.mod"
         );
     }
+
+    #[test]
+    #[ignore = "hits modarchive.org"]
+    fn artists() {
+        let modinfo = ModInfo::get(61772).unwrap();
+        let yrde = modinfo
+            .artists
+            .iter()
+            .find(|member| member.name == "Yrde")
+            .expect("7th Dance is credited to Yrde");
+
+        let artist = Artist::get(yrde.id).unwrap();
+        assert_eq!(artist.name, "Yrde");
+        assert!(artist.module_ids.contains(&61772));
+    }
+
+    #[test]
+    #[ignore = "hits modarchive.org"]
+    fn ratings() {
+        // a spotlit module has surely been rated by someone
+        let modinfo = ModInfo::get(158263).unwrap();
+        assert!(modinfo.member_votes > 0);
+        assert!(modinfo.member_rating.is_some());
+    }
+
+    #[test]
+    #[ignore = "hits modarchive.org"]
+    fn reviews() {
+        // an empty list is fine, a missing one is a NotFound
+        assert!(ModInfo::reviews(158263).is_ok());
+    }
+
+    #[test]
+    #[ignore = "hits modarchive.org"]
+    fn comments() {
+        let comments = ModInfo::comments(158263).unwrap();
+        assert!(!comments.is_empty());
+        assert!(comments
+            .iter()
+            .all(|comment| !comment.author.name.is_empty()));
+    }
+
+    #[test]
+    #[ignore = "hits modarchive.org"]
+    fn license() {
+        assert!(ModInfo::get(158263).unwrap().license.is_some());
+    }
 }
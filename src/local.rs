@@ -0,0 +1,789 @@
+//! Reads metadata straight out of module files, no network involved.
+//!
+//! Only the headers of ProTracker MOD (31 samples), ScreamTracker S3M, FastTracker XM and
+//! Impulse Tracker IT files are parsed, enough to get the same title, format, channel count and
+//! instrument text that [`ModInfo`](crate::ModInfo) scrapes off a module page. A parsed file can
+//! be checked against its archived [`ModInfo`](crate::ModInfo) with [`LocalModule::compare()`].
+//!
+//! ```rust
+//! use trackermeta::local::LocalModule;
+//!
+//! fn main() {
+//!     let module = LocalModule::from_path("7th_dance.xm").unwrap();
+//!     println!("{} ({}, {} channels)", module.title, module.format, module.channel_count);
+//!     println!("{}", module.instrument_text());
+//! }
+//! ```
+use std::fmt;
+use std::path::Path;
+
+use crate::{Md5Digest, ModArchiveClient, ModInfo, ModuleFormat, Transport};
+
+/// The metadata read from a module file, the fields are named after their counterparts in
+/// [`ModInfo`](crate::ModInfo)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LocalModule {
+    /// The song title stored in the header
+    pub title: String,
+    /// One of [`ModuleFormat::Mod`], [`ModuleFormat::S3m`], [`ModuleFormat::Xm`] or
+    /// [`ModuleFormat::It`]
+    pub format: ModuleFormat,
+    /// How many channels the module uses
+    pub channel_count: u32,
+    /// The instrument names, in order, empty for MOD and S3M which only have samples
+    pub instrument_names: Vec<String>,
+    /// The sample names, in order
+    pub sample_names: Vec<String>,
+    /// The size of the whole file in bytes
+    pub size: u64,
+    /// The MD5 of the whole file
+    pub md5: Md5Digest,
+}
+
+impl LocalModule {
+    /// Parses a module file that's already in memory, anything that isn't one of the supported
+    /// formats is an [`Error::Parse`](crate::Error::Parse) for the `format` field
+    pub fn parse(bytes: &[u8]) -> Result<LocalModule, crate::Error> {
+        let data = Data(bytes);
+
+        let header = if bytes.starts_with(b"Extended Module: ") {
+            parse_xm(data)?
+        } else if bytes.starts_with(b"IMPM") {
+            parse_it(data)?
+        } else if bytes.get(0x2c..0x30) == Some(b"SCRM") {
+            parse_s3m(data)?
+        } else if let Some(channel_count) = bytes.get(1080..1084).and_then(mod_channels) {
+            parse_mod(data, channel_count)?
+        } else {
+            return Err(crate::Error::Parse {
+                field: "format",
+                text: String::from_utf8_lossy(&bytes[..bytes.len().min(17)]).into_owned(),
+            });
+        };
+
+        Ok(LocalModule {
+            title: header.title,
+            format: header.format,
+            channel_count: header.channel_count,
+            instrument_names: header.instrument_names,
+            sample_names: header.sample_names,
+            size: bytes.len() as u64,
+            md5: Md5Digest::compute(bytes),
+        })
+    }
+
+    /// Reads and parses the module file at `path`
+    pub fn from_path(path: impl AsRef<Path>) -> Result<LocalModule, crate::Error> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|err| crate::Error::Io(format!("{}: {}", path.display(), err)))?;
+        LocalModule::parse(&bytes)
+    }
+
+    /// The instrument names followed by the sample names one per line, which is what Mod
+    /// Archive shows as the instrument text (see [`ModInfo::instrument_text`](crate::ModInfo::instrument_text))
+    pub fn instrument_text(&self) -> String {
+        self.instrument_names
+            .iter()
+            .chain(&self.sample_names)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+
+    /// Checks this file against the [`ModInfo`] scraped for the module it's supposed to be, a
+    /// different MD5 means the file isn't the archived one and the other fields show what
+    /// changed (an edited title, missing instruments and so on)
+    pub fn compare(&self, modinfo: &ModInfo) -> Comparison {
+        let mut mismatches = Vec::new();
+        let mut check = |field: Field, local: String, archived: String, same: bool| {
+            if !same {
+                mismatches.push(Mismatch {
+                    field,
+                    local,
+                    archived,
+                });
+            }
+        };
+
+        check(
+            Field::Md5,
+            self.md5.to_string(),
+            modinfo.md5.to_string(),
+            self.md5 == modinfo.md5,
+        );
+        check(
+            Field::Format,
+            self.format.to_string(),
+            modinfo.format.to_string(),
+            self.format == modinfo.format,
+        );
+        check(
+            Field::ChannelCount,
+            self.channel_count.to_string(),
+            modinfo.channel_count.to_string(),
+            self.channel_count == modinfo.channel_count,
+        );
+        check(
+            Field::Title,
+            self.title.clone(),
+            modinfo.title.clone(),
+            self.title.trim() == modinfo.title.trim(),
+        );
+
+        let instrument_text = self.instrument_text();
+        check(
+            Field::InstrumentText,
+            instrument_text.clone(),
+            modinfo.instrument_text.clone(),
+            same_text(&instrument_text, &modinfo.instrument_text),
+        );
+
+        Comparison {
+            id: modinfo.id,
+            mismatches,
+        }
+    }
+}
+
+/// Whether two instrument texts are the same once whitespace the site might have eaten (at the
+/// ends of lines and of the whole text) is ignored
+fn same_text(local: &str, archived: &str) -> bool {
+    local
+        .trim()
+        .lines()
+        .map(str::trim_end)
+        .eq(archived.trim().lines().map(str::trim_end))
+}
+
+/// What [`LocalModule::compare()`] found
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Comparison {
+    /// The ID of the module the file was compared against
+    pub id: u32,
+    /// Every field that differs, empty if the file matches
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl Comparison {
+    /// Whether the file matches the archived module in every field
+    pub fn is_match(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Whether the file's MD5 differs from the archived one, which means it's corrupted or
+    /// edited even if the header looks the same
+    pub fn hash_differs(&self) -> bool {
+        self.mismatch(Field::Md5).is_some()
+    }
+
+    /// The mismatch for `field`, if there is one
+    pub fn mismatch(&self, field: Field) -> Option<&Mismatch> {
+        self.mismatches
+            .iter()
+            .find(|mismatch| mismatch.field == field)
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_match() {
+            return write!(f, "matches module {}", self.id);
+        }
+
+        write!(f, "differs from module {}:", self.id)?;
+        for mismatch in &self.mismatches {
+            write!(f, "\n  {}", mismatch)?;
+        }
+        Ok(())
+    }
+}
+
+/// The fields [`LocalModule::compare()`] checks, named after their [`ModInfo`] counterparts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Field {
+    Md5,
+    Format,
+    ChannelCount,
+    Title,
+    InstrumentText,
+}
+
+impl Field {
+    /// The name of the [`ModInfo`] field
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Field::Md5 => "md5",
+            Field::Format => "format",
+            Field::ChannelCount => "channel_count",
+            Field::Title => "title",
+            Field::InstrumentText => "instrument_text",
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One field where a local file and the archived module disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mismatch {
+    /// The field that differs
+    pub field: Field,
+    /// The value in the local file
+    pub local: String,
+    /// The value on Mod Archive
+    pub archived: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {:?} locally but {:?} on mod archive",
+            self.field, self.local, self.archived
+        )
+    }
+}
+
+impl<T: Transport> ModArchiveClient<T> {
+    /// Parses the file at `path` and compares it against module `mod_id` as it is on Mod
+    /// Archive, see [`LocalModule::compare()`]
+    pub fn compare_file(
+        &self,
+        path: impl AsRef<Path>,
+        mod_id: u32,
+    ) -> Result<Comparison, crate::Error> {
+        let local = LocalModule::from_path(path)?;
+        Ok(local.compare(&self.get_mod(mod_id)?))
+    }
+}
+
+impl ModInfo {
+    /// Parses the file at `path` and compares it against this module, nothing is fetched, see
+    /// [`LocalModule::compare()`]
+    pub fn compare_file(&self, path: impl AsRef<Path>) -> Result<Comparison, crate::Error> {
+        Ok(LocalModule::from_path(path)?.compare(self))
+    }
+}
+
+struct Header {
+    title: String,
+    format: ModuleFormat,
+    channel_count: u32,
+    instrument_names: Vec<String>,
+    sample_names: Vec<String>,
+}
+
+/// Bounds checked little-endian reads, running off the end is an error for the `header` field
+#[derive(Clone, Copy)]
+struct Data<'a>(&'a [u8]);
+
+impl<'a> Data<'a> {
+    fn bytes(&self, offset: usize, len: usize) -> Result<&'a [u8], crate::Error> {
+        offset
+            .checked_add(len)
+            .and_then(|end| self.0.get(offset..end))
+            .ok_or_else(|| crate::Error::Parse {
+                field: "header",
+                text: format!(
+                    "needed {} bytes at offset {} but the file is {} bytes long",
+                    len,
+                    offset,
+                    self.0.len()
+                ),
+            })
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16, crate::Error> {
+        let bytes = self.bytes(offset, 2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32, crate::Error> {
+        let bytes = self.bytes(offset, 4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// A fixed size text field, trackers pad them with NULs or spaces and the bytes are read as
+    /// Latin-1 since there's no telling which codepage was used
+    fn text(&self, offset: usize, len: usize) -> Result<String, crate::Error> {
+        let bytes = self.bytes(offset, len)?;
+        let bytes = bytes.split(|&byte| byte == 0).next().unwrap_or_default();
+        Ok(bytes
+            .iter()
+            .map(|&byte| if byte < 0x20 { ' ' } else { byte as char })
+            .collect::<String>()
+            .trim_end()
+            .to_string())
+    }
+}
+
+/// The channel count a MOD signature stands for, `None` if it isn't a known one
+fn mod_channels(signature: &[u8]) -> Option<u32> {
+    let digits = |digits: &[u8]| std::str::from_utf8(digits).ok()?.parse().ok();
+
+    match signature {
+        b"M.K." | b"M!K!" | b"M&K!" | b"N.T." | b"FLT4" => Some(4),
+        b"FLT8" | b"CD81" | b"OKTA" | b"OCTA" => Some(8),
+        [b'T', b'D', b'Z', n] => digits(&[*n]),
+        [n, b'C', b'H', b'N'] => digits(&[*n]),
+        [a, b, b'C', b'H'] | [a, b, b'C', b'N'] => digits(&[*a, *b]),
+        _ => None,
+    }
+}
+
+fn parse_mod(data: Data, channel_count: u32) -> Result<Header, crate::Error> {
+    Ok(Header {
+        title: data.text(0, 20)?,
+        format: ModuleFormat::Mod,
+        channel_count,
+        instrument_names: Vec::new(),
+        sample_names: (0..31)
+            .map(|sample| data.text(20 + sample * 30, 22))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn parse_s3m(data: Data) -> Result<Header, crate::Error> {
+    let order_count = data.u16(0x20)? as usize;
+    let sample_count = data.u16(0x22)? as usize;
+
+    // 0-15 are the PCM channels and 16-31 the AdLib ones, anything with the top bit set is off
+    let channel_count = data
+        .bytes(0x40, 32)?
+        .iter()
+        .filter(|&&setting| setting < 32)
+        .count() as u32;
+
+    let sample_names = (0..sample_count)
+        .map(|sample| {
+            let offset = data.u16(0x60 + order_count + sample * 2)? as usize * 16;
+            data.text(offset + 0x30, 28)
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Header {
+        title: data.text(0, 28)?,
+        format: ModuleFormat::S3m,
+        channel_count,
+        instrument_names: Vec::new(),
+        sample_names,
+    })
+}
+
+/// The most instruments an XM file can hold and the most samples one of its instruments can
+/// have, a header claiming more is broken
+const XM_MAX_INSTRUMENTS: usize = 256;
+const XM_MAX_SAMPLES: usize = 16;
+
+/// The smallest instrument and sample headers an XM file can have, anything smaller would
+/// overlap the fields that are read out of them
+const XM_MIN_INSTRUMENT_HEADER: usize = 29;
+const XM_MIN_SAMPLE_HEADER: usize = 40;
+
+/// A broken XM header, for the `header` field like running off the end of the file
+fn xm_error(text: String) -> crate::Error {
+    crate::Error::Parse {
+        field: "header",
+        text,
+    }
+}
+
+/// Skips `len` bytes from `offset`, the sizes come from the file so they can add up to anything
+fn skip(offset: usize, len: usize) -> Result<usize, crate::Error> {
+    offset
+        .checked_add(len)
+        .ok_or_else(|| xm_error(format!("skipping {} bytes from offset {}", len, offset)))
+}
+
+fn parse_xm(data: Data) -> Result<Header, crate::Error> {
+    let header_size = data.u32(60)? as usize;
+    let channel_count = data.u16(68)? as u32;
+    let pattern_count = data.u16(70)? as usize;
+    let instrument_count = data.u16(72)? as usize;
+    if instrument_count > XM_MAX_INSTRUMENTS {
+        return Err(xm_error(format!("{} instruments", instrument_count)));
+    }
+
+    // the instruments come after the patterns, which have to be skipped over one by one
+    let mut offset = skip(60, header_size)?;
+    for _ in 0..pattern_count {
+        let header_size = data.u32(offset)? as usize;
+        let packed_size = data.u16(offset + 7)? as usize;
+        offset = skip(skip(offset, header_size)?, packed_size)?;
+    }
+
+    let mut instrument_names = Vec::with_capacity(instrument_count);
+    let mut sample_names = Vec::new();
+    for _ in 0..instrument_count {
+        let instrument_size = data.u32(offset)? as usize;
+        if instrument_size < XM_MIN_INSTRUMENT_HEADER {
+            return Err(xm_error(format!(
+                "{} byte instrument header at offset {}",
+                instrument_size, offset
+            )));
+        }
+        instrument_names.push(data.text(offset + 4, 22)?);
+
+        let sample_count = data.u16(offset + 27)? as usize;
+        if sample_count == 0 {
+            offset = skip(offset, instrument_size)?;
+            continue;
+        }
+        if sample_count > XM_MAX_SAMPLES {
+            return Err(xm_error(format!(
+                "{} samples in the instrument at offset {}",
+                sample_count, offset
+            )));
+        }
+
+        let sample_header_size = data.u32(offset + 29)? as usize;
+        if sample_header_size < XM_MIN_SAMPLE_HEADER {
+            return Err(xm_error(format!(
+                "{} byte sample headers at offset {}",
+                sample_header_size, offset
+            )));
+        }
+        offset = skip(offset, instrument_size)?;
+
+        let mut sample_data_size: usize = 0;
+        for _ in 0..sample_count {
+            sample_data_size = skip(sample_data_size, data.u32(offset)? as usize)?;
+            sample_names.push(data.text(offset + 18, 22)?);
+            offset = skip(offset, sample_header_size)?;
+        }
+        offset = skip(offset, sample_data_size)?;
+    }
+
+    Ok(Header {
+        title: data.text(17, 20)?,
+        format: ModuleFormat::Xm,
+        channel_count,
+        instrument_names,
+        sample_names,
+    })
+}
+
+fn parse_it(data: Data) -> Result<Header, crate::Error> {
+    let order_count = data.u16(0x20)? as usize;
+    let instrument_count = data.u16(0x22)? as usize;
+    let sample_count = data.u16(0x24)? as usize;
+    let pattern_count = data.u16(0x26)? as usize;
+
+    let offsets = |start: usize, count: usize| -> Result<Vec<usize>, crate::Error> {
+        (0..count)
+            .map(|index| Ok(data.u32(start + index * 4)? as usize))
+            .collect()
+    };
+    let instrument_offsets = offsets(0xc0 + order_count, instrument_count)?;
+    let sample_offsets = offsets(0xc0 + order_count + instrument_count * 4, sample_count)?;
+    let pattern_offsets = offsets(
+        0xc0 + order_count + (instrument_count + sample_count) * 4,
+        pattern_count,
+    )?;
+
+    // the header enables all 64 channels more often than not, so the channel count is taken
+    // from the highest channel the patterns actually use
+    let mut channel_count = 0;
+    for offset in pattern_offsets.into_iter().filter(|&offset| offset != 0) {
+        channel_count = channel_count.max(it_pattern_channels(data, offset)?);
+    }
+    if channel_count == 0 {
+        channel_count = data
+            .bytes(0x40, 64)?
+            .iter()
+            .filter(|&&pan| pan < 128)
+            .count() as u32;
+    }
+
+    Ok(Header {
+        title: data.text(4, 26)?,
+        format: ModuleFormat::It,
+        channel_count,
+        instrument_names: instrument_offsets
+            .into_iter()
+            .map(|offset| data.text(offset + 0x20, 26))
+            .collect::<Result<_, _>>()?,
+        sample_names: sample_offsets
+            .into_iter()
+            .map(|offset| data.text(offset + 0x14, 26))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// Walks a packed IT pattern and returns the highest channel (counting from 1) with anything in it
+fn it_pattern_channels(data: Data, offset: usize) -> Result<u32, crate::Error> {
+    let packed = data.bytes(offset + 8, data.u16(offset)? as usize)?;
+    let mut masks = [0u8; 64];
+    let mut highest = 0;
+    let mut position = 0;
+
+    while position < packed.len() {
+        let channel_variable = packed[position];
+        position += 1;
+        if channel_variable == 0 {
+            continue;
+        }
+
+        let channel = ((channel_variable - 1) & 63) as usize;
+        if channel_variable & 128 != 0 {
+            masks[channel] = *packed.get(position).unwrap_or(&0);
+            position += 1;
+        }
+
+        // note, instrument and volume are a byte each, the effect and its parameter two
+        let mask = masks[channel];
+        position += [(1, 1), (2, 1), (4, 1), (8, 2)]
+            .iter()
+            .filter(|(bit, _)| mask & bit != 0)
+            .map(|(_, size)| size)
+            .sum::<usize>();
+
+        highest = highest.max(channel as u32 + 1);
+    }
+
+    Ok(highest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Field, LocalModule};
+    use crate::{Error, Md5Digest, ModInfo, ModuleFormat};
+
+    /// Writes `bytes` into `buf` at `offset`, growing it as needed
+    fn put(buf: &mut Vec<u8>, offset: usize, bytes: &[u8]) {
+        if buf.len() < offset + bytes.len() {
+            buf.resize(offset + bytes.len(), 0);
+        }
+        buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    #[test]
+    fn mod_header() {
+        let mut file = Vec::new();
+        put(&mut file, 0, b"space debris");
+        put(&mut file, 20, b"captain");
+        put(&mut file, 20 + 30, b"of space");
+        put(&mut file, 1080, b"8CHN");
+
+        let module = LocalModule::parse(&file).unwrap();
+        assert_eq!(module.title, "space debris");
+        assert_eq!(module.format, ModuleFormat::Mod);
+        assert_eq!(module.channel_count, 8);
+        assert_eq!(module.sample_names.len(), 31);
+        assert_eq!(module.instrument_text(), "captain\nof space");
+        assert_eq!(module.size, 1084);
+
+        put(&mut file, 1080, b"M.K.");
+        assert_eq!(LocalModule::parse(&file).unwrap().channel_count, 4);
+        put(&mut file, 1080, b"12CH");
+        assert_eq!(LocalModule::parse(&file).unwrap().channel_count, 12);
+    }
+
+    #[test]
+    fn s3m_header() {
+        let mut file = Vec::new();
+        put(&mut file, 0, b"second reality");
+        put(&mut file, 0x1c, &[0x1a, 16]);
+        put(&mut file, 0x20, &2u16.to_le_bytes());
+        put(&mut file, 0x22, &2u16.to_le_bytes());
+        put(&mut file, 0x2c, b"SCRM");
+        put(&mut file, 0x40, &[0, 1, 2, 3, 16, 255, 255, 255]);
+        put(&mut file, 0x48, &[255; 24]);
+        put(&mut file, 0x60, &[0, 255]);
+        put(&mut file, 0x62, &7u16.to_le_bytes());
+        put(&mut file, 0x64, &12u16.to_le_bytes());
+        put(&mut file, 7 * 16 + 0x30, b"bassdrum");
+        put(&mut file, 12 * 16 + 0x30, b"snare");
+        put(&mut file, 12 * 16 + 0x4c, b"SCRS");
+
+        let module = LocalModule::parse(&file).unwrap();
+        assert_eq!(module.title, "second reality");
+        assert_eq!(module.format, ModuleFormat::S3m);
+        assert_eq!(module.channel_count, 5);
+        assert_eq!(module.sample_names, ["bassdrum", "snare"]);
+    }
+
+    #[test]
+    fn xm_header() {
+        let mut file = Vec::new();
+        put(&mut file, 0, b"Extended Module: 7th Dance");
+        put(&mut file, 37, &[0x1a]);
+        put(&mut file, 60, &276u32.to_le_bytes());
+        put(&mut file, 68, &16u16.to_le_bytes());
+        put(&mut file, 70, &1u16.to_le_bytes());
+        put(&mut file, 72, &2u16.to_le_bytes());
+
+        // one pattern with 3 bytes of packed data
+        let pattern = 60 + 276;
+        put(&mut file, pattern, &9u32.to_le_bytes());
+        put(&mut file, pattern + 7, &3u16.to_le_bytes());
+
+        // an instrument with one 5 byte sample, then one without samples
+        let instrument = pattern + 9 + 3;
+        put(&mut file, instrument, &263u32.to_le_bytes());
+        put(&mut file, instrument + 4, b"7th  Dance");
+        put(&mut file, instrument + 27, &1u16.to_le_bytes());
+        put(&mut file, instrument + 29, &40u32.to_le_bytes());
+        let sample = instrument + 263;
+        put(&mut file, sample, &5u32.to_le_bytes());
+        put(&mut file, sample + 18, b"kick");
+        let instrument = sample + 40 + 5;
+        put(&mut file, instrument, &29u32.to_le_bytes());
+        put(&mut file, instrument + 4, b"     By:");
+        put(&mut file, instrument + 28, &[0]);
+
+        let module = LocalModule::parse(&file).unwrap();
+        assert_eq!(module.title, "7th Dance");
+        assert_eq!(module.format, ModuleFormat::Xm);
+        assert_eq!(module.channel_count, 16);
+        assert_eq!(module.instrument_names, ["7th  Dance", "     By:"]);
+        assert_eq!(module.sample_names, ["kick"]);
+        assert_eq!(module.instrument_text(), "7th  Dance\n     By:\nkick");
+    }
+
+    #[test]
+    fn malformed_xm_header() {
+        /// An XM without patterns whose instruments all claim `sample_count` samples, with the
+        /// instrument and sample header sizes given
+        fn xm(instrument_count: u16, sample_count: u16, header_sizes: (u32, u32)) -> Vec<u8> {
+            let mut file = Vec::new();
+            put(&mut file, 0, b"Extended Module: broken");
+            put(&mut file, 37, &[0x1a]);
+            put(&mut file, 60, &20u32.to_le_bytes());
+            put(&mut file, 72, &instrument_count.to_le_bytes());
+            put(&mut file, 80, &header_sizes.0.to_le_bytes());
+            put(&mut file, 80 + 27, &sample_count.to_le_bytes());
+            put(&mut file, 80 + 29, &header_sizes.1.to_le_bytes());
+            put(&mut file, 80 + 29 + 4, &[0]);
+            file
+        }
+
+        fn header_error(file: &[u8]) -> bool {
+            matches!(
+                LocalModule::parse(file),
+                Err(Error::Parse {
+                    field: "header",
+                    ..
+                })
+            )
+        }
+
+        // a tiny file that used to go through billions of zero sized sample headers
+        assert!(header_error(&xm(u16::MAX, u16::MAX, (33, 0))));
+
+        assert!(header_error(&xm(257, 1, (33, 40))));
+        assert!(header_error(&xm(1, 17, (33, 40))));
+        assert!(header_error(&xm(1, 1, (0, 40))));
+        assert!(header_error(&xm(1, 1, (33, 39))));
+
+        // a header size pointing way past the end of the file
+        let mut file = xm(1, 1, (33, 40));
+        put(&mut file, 60, &u32::MAX.to_le_bytes());
+        assert!(header_error(&file));
+    }
+
+    #[test]
+    fn it_header() {
+        let mut file = Vec::new();
+        put(&mut file, 0, b"IMPMhybrid song");
+        put(&mut file, 0x20, &1u16.to_le_bytes());
+        put(&mut file, 0x22, &1u16.to_le_bytes());
+        put(&mut file, 0x24, &1u16.to_le_bytes());
+        put(&mut file, 0x26, &1u16.to_le_bytes());
+        put(&mut file, 0x40, &[32; 64]);
+        put(&mut file, 0xc0, &[0]);
+
+        let instrument = 0x200;
+        let sample = 0x300;
+        let pattern = 0x400;
+        put(&mut file, 0xc1, &(instrument as u32).to_le_bytes());
+        put(&mut file, 0xc5, &(sample as u32).to_le_bytes());
+        put(&mut file, 0xc9, &(pattern as u32).to_le_bytes());
+        put(&mut file, instrument, b"IMPI");
+        put(&mut file, instrument + 0x20, b"lead");
+        put(&mut file, sample, b"IMPS");
+        put(&mut file, sample + 0x14, b"saw wave");
+
+        // channel 1 with a note and instrument, then channel 6 reusing its mask, end of row
+        let packed = [0x81, 0x03, 60, 1, 0x86, 0x01, 62, 0x81, 0x01, 64, 0];
+        put(&mut file, pattern, &(packed.len() as u16).to_le_bytes());
+        put(&mut file, pattern + 2, &64u16.to_le_bytes());
+        put(&mut file, pattern + 8, &packed);
+
+        let module = LocalModule::parse(&file).unwrap();
+        assert_eq!(module.title, "hybrid song");
+        assert_eq!(module.format, ModuleFormat::It);
+        assert_eq!(module.channel_count, 6);
+        assert_eq!(module.instrument_text(), "lead\nsaw wave");
+    }
+
+    #[test]
+    fn unknown_and_truncated() {
+        assert!(matches!(
+            LocalModule::parse(b"not a module"),
+            Err(Error::Parse {
+                field: "format",
+                ..
+            })
+        ));
+        assert!(matches!(
+            LocalModule::parse(b"IMPMtruncated"),
+            Err(Error::Parse {
+                field: "header",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn compare_against_modinfo() {
+        let modinfo =
+            ModInfo::from_html(61772, include_str!("../tests/fixtures/module.html")).unwrap();
+        let mut local = LocalModule {
+            title: "7th Dance".into(),
+            format: ModuleFormat::Xm,
+            channel_count: 16,
+            instrument_names: modinfo
+                .instrument_text
+                .lines()
+                .map(|line| format!("{}   ", line))
+                .collect(),
+            sample_names: vec![String::new(); 4],
+            size: modinfo.size,
+            md5: modinfo.md5,
+        };
+
+        let comparison = local.compare(&modinfo);
+        assert!(comparison.is_match(), "{}", comparison);
+
+        local.md5 = Md5Digest([0; 16]);
+        local.channel_count = 8;
+        local.instrument_names.truncate(1);
+        let comparison = local.compare(&modinfo);
+        assert!(comparison.hash_differs());
+        assert_eq!(
+            comparison
+                .mismatches
+                .iter()
+                .map(|mismatch| mismatch.field)
+                .collect::<Vec<_>>(),
+            [Field::Md5, Field::ChannelCount, Field::InstrumentText]
+        );
+        assert_eq!(comparison.mismatch(Field::ChannelCount).unwrap().local, "8");
+        assert!(comparison
+            .to_string()
+            .starts_with("differs from module 61772:\n  md5: "));
+    }
+}
@@ -0,0 +1,567 @@
+//! Async versions of the scraping functions, only available with the `async` feature.
+//!
+//! Everything here goes through the same parsing code as the blocking API, only the fetching
+//! is different. The download links ([`ModInfo::get_download_link()`] and
+//! [`ModSearch::get_download_link()`]) don't do any I/O so they work the same in both worlds.
+//!
+//! ```rust
+//! use trackermeta::nonblocking::AsyncModArchiveClient;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = AsyncModArchiveClient::new().unwrap();
+//!     let modinfo = client.get_mod(51772).await.unwrap();
+//!     println!("{:#?}", modinfo);
+//! }
+//! ```
+use std::future::Future;
+
+use chrono::Utc;
+
+use crate::cache::{self, CachedPage};
+use crate::client::{check_status, landed_mod, verify_download, Urls};
+use crate::random::{pick_page, pick_result};
+use crate::search::{Listing, PageWalk};
+use crate::{
+    parse, Artist, ClientBuilder, Comment, Genre, Md5Digest, ModInfo, ModSearch, ModuleFormat,
+    RandomFilter, RateLimiter, Response, ResponseCache, RetryPolicy, Review, SearchPage,
+    SearchQuery,
+};
+
+/// The async counterpart of [`Transport`](crate::Transport)
+pub trait AsyncTransport {
+    /// Does a `GET` request to `url` and returns whatever came back, a non-success status is
+    /// still an [`Ok`].
+    fn fetch(&self, url: &str) -> impl Future<Output = Result<Response, crate::Error>> + Send;
+}
+
+impl<T: AsyncTransport + Sync + ?Sized> AsyncTransport for &T {
+    fn fetch(&self, url: &str) -> impl Future<Output = Result<Response, crate::Error>> + Send {
+        (**self).fetch(url)
+    }
+}
+
+impl<T: AsyncTransport + Sync + ?Sized> AsyncTransport for std::sync::Arc<T> {
+    fn fetch(&self, url: &str) -> impl Future<Output = Result<Response, crate::Error>> + Send {
+        (**self).fetch(url)
+    }
+}
+
+/// The default [`AsyncTransport`], a thin wrapper over a [`reqwest::Client`]
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Wraps an already configured client
+    pub fn new(client: reqwest::Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+impl ReqwestTransport {
+    /// Uses the same timeouts and user agent as [`ClientBuilder::new()`](crate::ClientBuilder::new),
+    /// fails if reqwest can't set up its client (TLS initialization for example)
+    pub fn with_defaults() -> Result<Self, crate::Error> {
+        ClientBuilder::new().reqwest_transport()
+    }
+}
+
+impl From<reqwest::Error> for crate::Error {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            crate::Error::Timeout
+        } else if err.is_connect() || err.is_request() {
+            crate::Error::Connection(err.to_string())
+        } else if err.is_body() || err.is_decode() {
+            crate::Error::Decode(err.to_string())
+        } else {
+            crate::Error::Transport(err.to_string())
+        }
+    }
+}
+
+impl AsyncTransport for ReqwestTransport {
+    async fn fetch(&self, url: &str) -> Result<Response, crate::Error> {
+        let response = self.client.get(url).send().await?;
+        let status = response.status().as_u16();
+        let body = response.bytes().await?.to_vec();
+
+        Ok(Response { status, body })
+    }
+}
+
+/// The async counterpart of [`ModArchiveClient`](crate::ModArchiveClient)
+#[derive(Debug, Clone)]
+pub struct AsyncModArchiveClient<T = ReqwestTransport> {
+    transport: T,
+    urls: Urls,
+    retry: RetryPolicy,
+    limiter: RateLimiter,
+    cache: Option<ResponseCache>,
+}
+
+impl AsyncModArchiveClient {
+    /// Creates a client with all of the defaults, it shares the process-wide [`RateLimiter`]
+    /// with the free functions and the blocking default clients. Use
+    /// [`ClientBuilder::build_async()`](crate::ClientBuilder::build_async) for anything custom.
+    ///
+    /// Fails if reqwest can't set up its client (TLS initialization for example).
+    pub fn new() -> Result<Self, crate::Error> {
+        ClientBuilder::new().build_async()
+    }
+}
+
+impl<T: AsyncTransport> AsyncModArchiveClient<T> {
+    /// Creates a client that does its requests through `transport`, it gets its own
+    /// [`RateLimiter`] with the default rate of 1 request per second.
+    pub fn with_transport(transport: T) -> Self {
+        AsyncModArchiveClient::from_parts(
+            transport,
+            Urls::default(),
+            RetryPolicy::default(),
+            RateLimiter::default(),
+            None,
+        )
+    }
+
+    pub(crate) fn from_parts(
+        transport: T,
+        urls: Urls,
+        retry: RetryPolicy,
+        limiter: RateLimiter,
+        cache: Option<ResponseCache>,
+    ) -> Self {
+        AsyncModArchiveClient {
+            transport,
+            urls,
+            retry,
+            limiter,
+            cache,
+        }
+    }
+
+    /// The transport this client is using
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Uses `policy` for retrying failed requests instead of the default [`RetryPolicy`]
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// The retry policy this client is using
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// Throttles this client with `limiter` instead, the same limiter can be shared with
+    /// blocking clients too
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// The rate limiter this client is using
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+
+    /// Stores fetched pages in `cache` and answers from it when it can, the cache does plain
+    /// blocking file I/O but the pages are small
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// The response cache this client is using, if any
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+
+    async fn fetch_page(&self, url: &str) -> Result<String, crate::Error> {
+        Ok(self.fetch_cached(url, false).await?.body)
+    }
+
+    async fn fetch_cached(&self, url: &str, refresh: bool) -> Result<CachedPage, crate::Error> {
+        if let Some(page) = cache::lookup(self.cache.as_ref(), url, refresh)? {
+            return Ok(page);
+        }
+
+        let fetched = Utc::now();
+        let body = self.fetch_uncached(url).await?;
+        cache::store(self.cache.as_ref(), url, &body, fetched);
+
+        Ok(CachedPage { body, fetched })
+    }
+
+    async fn fetch_uncached(&self, url: &str) -> Result<String, crate::Error> {
+        self.fetch_ok(url).await?.text()
+    }
+
+    /// Fetches `url` through the limiter and the retry policy, non-success statuses are errors
+    async fn fetch_ok(&self, url: &str) -> Result<Response, crate::Error> {
+        let mut attempt = 1;
+
+        loop {
+            let delay = self.limiter.reserve();
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+
+            let response = self.transport.fetch(url).await.and_then(|response| {
+                check_status(response.status)?;
+                Ok(response)
+            });
+
+            match response {
+                Err(err) if self.retry.should_retry(&err, attempt) => {
+                    tokio::time::sleep(self.retry.delay(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Async version of [`ModArchiveClient::get_mod()`](crate::ModArchiveClient::get_mod)
+    pub async fn get_mod(&self, mod_id: u32) -> Result<ModInfo, crate::Error> {
+        self.fetch_mod(mod_id, false).await
+    }
+
+    /// Async version of [`ModArchiveClient::refresh_mod()`](crate::ModArchiveClient::refresh_mod)
+    pub async fn refresh_mod(&self, mod_id: u32) -> Result<ModInfo, crate::Error> {
+        self.fetch_mod(mod_id, true).await
+    }
+
+    async fn fetch_mod(&self, mod_id: u32, refresh: bool) -> Result<ModInfo, crate::Error> {
+        self.fetch_cached(&self.urls.mod_page(mod_id), refresh)
+            .await?
+            .into_mod(mod_id)
+    }
+
+    /// Async version of [`ModArchiveClient::random_mod()`](crate::ModArchiveClient::random_mod)
+    pub async fn random_mod(&self) -> Result<ModInfo, crate::Error> {
+        self.fetch_landing_mod(&self.urls.random(None)).await
+    }
+
+    /// Async version of
+    /// [`ModArchiveClient::random_mod_matching()`](crate::ModArchiveClient::random_mod_matching)
+    pub async fn random_mod_matching(
+        &self,
+        filter: &RandomFilter,
+    ) -> Result<ModInfo, crate::Error> {
+        let modinfo = self
+            .fetch_landing_mod(&self.urls.random(Some(filter)))
+            .await?;
+        if filter.matches(&modinfo) {
+            return Ok(modinfo);
+        }
+
+        let listing = filter.listing();
+        let first = self.listing_page(&listing, 1).await?;
+        let page = match pick_page(&first) {
+            1 => first,
+            page => self.listing_page(&listing, page).await?,
+        };
+        self.get_mod(pick_result(&page)?).await
+    }
+
+    async fn fetch_landing_mod(&self, url: &str) -> Result<ModInfo, crate::Error> {
+        cache::ensure_online(self.cache.as_ref())?;
+
+        let fetched = Utc::now();
+        let body = self.fetch_uncached(url).await?;
+        landed_mod(
+            self.cache.as_ref(),
+            &self.urls,
+            CachedPage { body, fetched },
+        )
+    }
+
+    /// Async version of [`ModArchiveClient::download()`](crate::ModArchiveClient::download),
+    /// the file is held in memory in full before it's checked
+    pub async fn download(&self, modinfo: &ModInfo) -> Result<Vec<u8>, crate::Error> {
+        cache::ensure_online(self.cache.as_ref())?;
+
+        let bytes = self.fetch_ok(&self.urls.download(modinfo.id)).await?.body;
+        verify_download(modinfo, Md5Digest::compute(&bytes))?;
+        Ok(bytes)
+    }
+
+    /// Async version of
+    /// [`ModArchiveClient::resolve_filename()`](crate::ModArchiveClient::resolve_filename)
+    pub async fn resolve_filename(&self, filename: &str) -> Result<Vec<ModSearch>, crate::Error> {
+        Ok(self.resolve_filename_page(filename, 1).await?.results)
+    }
+
+    /// Async version of
+    /// [`ModArchiveClient::resolve_filename_page()`](crate::ModArchiveClient::resolve_filename_page)
+    pub async fn resolve_filename_page(
+        &self,
+        filename: &str,
+        page: u32,
+    ) -> Result<SearchPage, crate::Error> {
+        self.search(&SearchQuery::new(filename).page(page)).await
+    }
+
+    /// Async version of [`ModArchiveClient::search()`](crate::ModArchiveClient::search)
+    pub async fn search(&self, query: &SearchQuery) -> Result<SearchPage, crate::Error> {
+        self.listing_page(&Listing::Search(query.clone()), query.page_number())
+            .await
+    }
+
+    /// Async version of [`ModArchiveClient::get_reviews()`](crate::ModArchiveClient::get_reviews)
+    pub async fn get_reviews(&self, mod_id: u32) -> Result<Vec<Review>, crate::Error> {
+        Review::from_html(&self.fetch_page(&self.urls.reviews(mod_id)).await?)
+    }
+
+    /// Async version of [`ModArchiveClient::get_comments()`](crate::ModArchiveClient::get_comments)
+    pub async fn get_comments(&self, mod_id: u32) -> Result<Vec<Comment>, crate::Error> {
+        let mut comments = Vec::new();
+        let mut walk = PageWalk::new(1);
+        while let Some(page) = walk.next_page() {
+            let current =
+                parse::comments(&self.fetch_page(&self.urls.comments(mod_id, page)).await?)?;
+            if walk.record(current.page, current.page_count) {
+                comments.extend(current.comments);
+            }
+        }
+        Ok(comments)
+    }
+
+    /// Async version of [`ModArchiveClient::genre_page()`](crate::ModArchiveClient::genre_page)
+    pub async fn genre_page(&self, genre: Genre, page: u32) -> Result<SearchPage, crate::Error> {
+        self.listing_page(&Listing::Genre(genre), page).await
+    }
+
+    /// Async version of [`ModArchiveClient::format_page()`](crate::ModArchiveClient::format_page)
+    pub async fn format_page(
+        &self,
+        format: ModuleFormat,
+        page: u32,
+    ) -> Result<SearchPage, crate::Error> {
+        self.listing_page(&Listing::Format(format), page).await
+    }
+
+    /// Async version of [`ModArchiveClient::uploaded_page()`](crate::ModArchiveClient::uploaded_page)
+    pub async fn uploaded_page(
+        &self,
+        year: i32,
+        month: Option<u32>,
+        page: u32,
+    ) -> Result<SearchPage, crate::Error> {
+        self.listing_page(&Listing::Uploaded { year, month }, page)
+            .await
+    }
+
+    /// Async version of [`ModArchiveClient::get_artist()`](crate::ModArchiveClient::get_artist)
+    pub async fn get_artist(&self, member_id: u32) -> Result<Artist, crate::Error> {
+        let profile = self.fetch_page(&self.urls.profile(member_id)).await?;
+        let mut artist = Artist::from_html(member_id, &profile)?;
+
+        let listing = Listing::ArtistModules(member_id);
+        let mut walk = PageWalk::new(1);
+        while let Some(page) = walk.next_page() {
+            let modules = self.listing_page(&listing, page).await?;
+            if walk.record(modules.page, modules.page_count) {
+                artist
+                    .module_ids
+                    .extend(modules.results.iter().map(|result| result.id));
+            }
+        }
+        Ok(artist)
+    }
+
+    async fn listing_page(&self, listing: &Listing, page: u32) -> Result<SearchPage, crate::Error> {
+        listing.check()?;
+        SearchPage::from_html(&self.fetch_page(&self.urls.listing(listing, page)).await?)
+    }
+}
+
+impl ModInfo {
+    /// Async version of [`ModInfo::get()`], goes through a default [`AsyncModArchiveClient`]
+    pub async fn get_async(mod_id: u32) -> Result<ModInfo, crate::Error> {
+        AsyncModArchiveClient::new()?.get_mod(mod_id).await
+    }
+
+    /// Async version of [`ModInfo::resolve_filename()`], goes through a default
+    /// [`AsyncModArchiveClient`]
+    pub async fn resolve_filename_async(filename: &str) -> Result<Vec<ModSearch>, crate::Error> {
+        AsyncModArchiveClient::new()?
+            .resolve_filename(filename)
+            .await
+    }
+
+    /// Async version of [`ModInfo::search()`], goes through a default [`AsyncModArchiveClient`]
+    pub async fn search_async(query: &SearchQuery) -> Result<SearchPage, crate::Error> {
+        AsyncModArchiveClient::new()?.search(query).await
+    }
+
+    /// Async version of [`ModInfo::genre_page()`], goes through a default [`AsyncModArchiveClient`]
+    pub async fn genre_page_async(genre: Genre, page: u32) -> Result<SearchPage, crate::Error> {
+        AsyncModArchiveClient::new()?.genre_page(genre, page).await
+    }
+
+    /// Async version of [`ModInfo::random()`], goes through a default [`AsyncModArchiveClient`]
+    pub async fn random_async() -> Result<ModInfo, crate::Error> {
+        AsyncModArchiveClient::new()?.random_mod().await
+    }
+
+    /// Async version of [`ModInfo::random_matching()`], goes through a default
+    /// [`AsyncModArchiveClient`]
+    pub async fn random_matching_async(filter: &RandomFilter) -> Result<ModInfo, crate::Error> {
+        AsyncModArchiveClient::new()?
+            .random_mod_matching(filter)
+            .await
+    }
+
+    /// Async version of [`ModInfo::format_page()`], goes through a default [`AsyncModArchiveClient`]
+    pub async fn format_page_async(
+        format: ModuleFormat,
+        page: u32,
+    ) -> Result<SearchPage, crate::Error> {
+        AsyncModArchiveClient::new()?
+            .format_page(format, page)
+            .await
+    }
+
+    /// Async version of [`ModInfo::uploaded_page()`], goes through a default
+    /// [`AsyncModArchiveClient`]
+    pub async fn uploaded_page_async(
+        year: i32,
+        month: Option<u32>,
+        page: u32,
+    ) -> Result<SearchPage, crate::Error> {
+        AsyncModArchiveClient::new()?
+            .uploaded_page(year, month, page)
+            .await
+    }
+
+    /// Async version of [`ModInfo::download()`], goes through a default
+    /// [`AsyncModArchiveClient`]
+    pub async fn download_async(&self) -> Result<Vec<u8>, crate::Error> {
+        AsyncModArchiveClient::new()?.download(self).await
+    }
+
+    /// Async version of [`ModInfo::reviews()`], goes through a default [`AsyncModArchiveClient`]
+    pub async fn reviews_async(mod_id: u32) -> Result<Vec<Review>, crate::Error> {
+        AsyncModArchiveClient::new()?.get_reviews(mod_id).await
+    }
+
+    /// Async version of [`ModInfo::comments()`], goes through a default [`AsyncModArchiveClient`]
+    pub async fn comments_async(mod_id: u32) -> Result<Vec<Comment>, crate::Error> {
+        AsyncModArchiveClient::new()?.get_comments(mod_id).await
+    }
+}
+
+impl Artist {
+    /// Async version of [`Artist::get()`], goes through a default [`AsyncModArchiveClient`]
+    pub async fn get_async(member_id: u32) -> Result<Artist, crate::Error> {
+        AsyncModArchiveClient::new()?.get_artist(member_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AsyncModArchiveClient, AsyncTransport};
+    use crate::client::DEFAULT_DOWNLOAD_URL;
+    use crate::test_support::{artist_site, comment_pages, fixture_async_client};
+    use crate::{Error, Md5Digest, ModInfo, ModuleFormat, Response, ResponseCache, RetryPolicy};
+
+    const MODULE: &str = include_str!("../tests/fixtures/module.html");
+    const MODULE_FILE: &str = "Extended Module: 7th Dance";
+
+    struct StatusTransport(u16);
+
+    impl AsyncTransport for StatusTransport {
+        async fn fetch(&self, _url: &str) -> Result<Response, Error> {
+            Ok(Response::new(self.0, "<html></html>"))
+        }
+    }
+
+    #[tokio::test]
+    async fn status_is_surfaced() {
+        let client = AsyncModArchiveClient::with_transport(StatusTransport(502))
+            .with_retry_policy(RetryPolicy::none());
+        assert_eq!(
+            client.resolve_filename("a.mod").await.unwrap_err(),
+            Error::Status(502)
+        );
+    }
+
+    #[tokio::test]
+    async fn gets_modules_through_the_cache() {
+        let dir = std::env::temp_dir().join(format!("trackermeta-async-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let client = fixture_async_client(|url| {
+            url.ends_with("request=view_by_moduleid&query=61772")
+                .then(|| MODULE.to_string())
+        })
+        .with_cache(ResponseCache::new(&dir));
+
+        let modinfo = client.get_mod(61772).await.unwrap();
+        assert_eq!(modinfo.id, 61772);
+        assert_eq!(modinfo.format, ModuleFormat::Xm);
+        assert_eq!(client.get_mod(1).await.unwrap_err(), Error::Status(404));
+
+        // the cached page is all an offline client needs
+        let offline =
+            fixture_async_client(|_| None).with_cache(ResponseCache::new(&dir).offline(true));
+        assert_eq!(offline.get_mod(61772).await.unwrap(), modinfo);
+        assert_eq!(offline.get_mod(1).await.unwrap_err(), Error::Offline);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn download_is_verified() {
+        let client = fixture_async_client(|url| {
+            url.starts_with(DEFAULT_DOWNLOAD_URL)
+                .then(|| MODULE_FILE.to_string())
+        });
+        let mut modinfo = ModInfo::from_html(61772, MODULE).unwrap();
+
+        modinfo.md5 = Md5Digest::compute(MODULE_FILE.as_bytes());
+        assert_eq!(
+            client.download(&modinfo).await.unwrap(),
+            MODULE_FILE.as_bytes()
+        );
+
+        modinfo.md5 = Md5Digest([0; 16]);
+        assert_eq!(
+            client.download(&modinfo).await.unwrap_err(),
+            Error::ChecksumMismatch {
+                expected: Md5Digest([0; 16]),
+                actual: Md5Digest::compute(MODULE_FILE.as_bytes()),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn follows_every_page() {
+        let client = fixture_async_client(comment_pages);
+        let authors: Vec<u32> = client
+            .get_comments(61772)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|comment| comment.author.id)
+            .collect();
+        assert_eq!(authors, [1234, 9012, 3456, 7890]);
+
+        let client = fixture_async_client(artist_site);
+        let artist = client.get_artist(69141).await.unwrap();
+        assert_eq!(artist.name, "Yrde");
+        assert_eq!(artist.module_ids, [61772, 61790, 61800, 61801]);
+
+        // a site that ignores the page number doesn't keep the walk going
+        let client = fixture_async_client(|_| {
+            Some(include_str!("../tests/fixtures/module_comments.html").to_string())
+        });
+        assert_eq!(client.get_comments(61772).await.unwrap().len(), 2);
+    }
+}
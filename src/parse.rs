@@ -0,0 +1,657 @@
+use crate::comment::CommentPage;
+use crate::{Artist, Comment, License, Member, ModInfo, ModSearch, Rating, Review, SearchPage};
+use chrono::prelude::{DateTime, NaiveDate, NaiveTime, Utc};
+
+/// Gets the inner text of the `n`th `li.stats` element on a module page, the `field` is only
+/// used to name what went missing in the error.
+fn nth_stat(dom: &tl::VDom, n: usize, field: &'static str) -> Result<String, crate::Error> {
+    let parser = dom.parser();
+    dom.query_selector("li.stats")
+        .and_then(|mut iter| iter.nth(n))
+        .and_then(|handle| handle.get(parser))
+        .map(|node| node.inner_text(parser).into_owned())
+        .ok_or(crate::Error::MissingField(field))
+}
+
+fn parse_field<T: std::str::FromStr>(field: &'static str, text: &str) -> Result<T, crate::Error> {
+    text.trim().parse().map_err(|_| crate::Error::Parse {
+        field,
+        text: text.into(),
+    })
+}
+
+fn decode_field(field: &'static str, text: &str) -> Result<String, crate::Error> {
+    escaper::decode_html(text).map_err(|_| crate::Error::Parse {
+        field,
+        text: text.into(),
+    })
+}
+
+/// Parses sizes like `204.54 KB` into bytes, the site uses binary units
+fn parse_size(field: &'static str, text: &str) -> Result<u64, crate::Error> {
+    let invalid = || crate::Error::Parse {
+        field,
+        text: text.into(),
+    };
+
+    let mut parts = text.split_whitespace();
+    let number: f64 = parts
+        .next()
+        .and_then(|number| number.replace(',', "").parse().ok())
+        .ok_or_else(invalid)?;
+
+    let multiplier = match parts
+        .next()
+        .map(|unit| unit.to_ascii_uppercase())
+        .as_deref()
+    {
+        None | Some("B") | Some("BYTES") => 1u64,
+        Some("KB") => 1 << 10,
+        Some("MB") => 1 << 20,
+        Some("GB") => 1 << 30,
+        Some(_) => return Err(invalid()),
+    };
+
+    Ok((number * multiplier as f64).round() as u64)
+}
+
+/// Parses dates the way the site writes them, like `Thu 7th Dec 2000`, into midnight UTC of
+/// that day. The weekday is optional and ignored.
+pub(crate) fn parse_date(field: &'static str, text: &str) -> Result<DateTime<Utc>, crate::Error> {
+    let invalid = || crate::Error::Parse {
+        field,
+        text: text.into(),
+    };
+
+    let parts: Vec<&str> = text.split_whitespace().collect();
+    let [day, month, year] = match parts.as_slice() {
+        [_, day, month, year] | [day, month, year] => [*day, *month, *year],
+        _ => return Err(invalid()),
+    };
+    let day = day.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+
+    NaiveDate::parse_from_str(&format!("{} {} {}", day, month, year), "%d %b %Y")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|datetime| datetime.and_utc())
+        .ok_or_else(invalid)
+}
+
+/// Parses timestamps like `Wed 4th Apr 2001 21:07`, a date as [`parse_date()`] takes it
+/// followed by the time of day
+fn parse_timestamp(field: &'static str, text: &str) -> Result<DateTime<Utc>, crate::Error> {
+    let invalid = || crate::Error::Parse {
+        field,
+        text: text.into(),
+    };
+
+    let (date, time) = text.trim().rsplit_once(' ').ok_or_else(invalid)?;
+    let time = NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| invalid())?;
+    let date = parse_date(field, date).map_err(|_| invalid())?;
+
+    Ok(date.date_naive().and_time(time).and_utc())
+}
+
+fn parse_dom(body: &str) -> Result<tl::VDom<'_>, crate::Error> {
+    tl::parse(body, tl::ParserOptions::default())
+        .map_err(|err| crate::Error::Decode(err.to_string()))
+}
+
+/// The ID of the module a module page is for, read from its download ID since the URL it was
+/// fetched from doesn't always have it
+pub(crate) fn mod_page_id(body: &str) -> Result<u32, crate::Error> {
+    let dom = parse_dom(body)?;
+
+    if dom
+        .get_elements_by_class_name("mod-page-archive-info")
+        .next()
+        .is_none()
+    {
+        return Err(crate::Error::NotFound);
+    }
+
+    first_number("id", &nth_stat(&dom, 1, "id")?)
+}
+
+/// Runs the extraction over the body of a module page, `mod_id` is the ID the page was
+/// requested for.
+pub(crate) fn mod_info(mod_id: u32, body: &str) -> Result<ModInfo, crate::Error> {
+    let dom = parse_dom(body)?;
+    let parser = dom.parser();
+
+    let id = mod_id;
+    let scrape_time = Utc::now();
+
+    let valid = dom
+        .get_elements_by_class_name("mod-page-archive-info")
+        .next()
+        .is_some();
+
+    if !valid {
+        return Err(crate::Error::NotFound);
+    }
+
+    let filename = {
+        dom.get_elements_by_class_name("module-sub-header")
+            .next()
+            .and_then(|handle| handle.get(parser))
+            .ok_or(crate::Error::MissingField("filename"))?
+            .inner_text(parser)
+            .replace(['(', ')'], "")
+    };
+
+    let title = {
+        decode_field(
+            "title",
+            &dom.query_selector("h1")
+                .and_then(|mut iter| iter.next())
+                .and_then(|handle| handle.get(parser))
+                .ok_or(crate::Error::MissingField("title"))?
+                .inner_text(parser)
+                .replace(&format!(" ({})", &filename), ""),
+        )?
+    };
+
+    // the 8th hit (nth starts from 0)
+    let size_text = nth_stat(&dom, 7, "size")?
+        .replace("Uncompressed Size: ", "")
+        .trim()
+        .to_string();
+    let size = parse_size("size", &size_text)?;
+
+    let md5 = nth_stat(&dom, 4, "md5")?.replace("MD5: ", "").parse()?;
+
+    let format = nth_stat(&dom, 5, "format")?.replace("Format: ", "").into();
+
+    let spotlit = dom
+        .get_elements_by_class_name("mod-page-featured")
+        .next()
+        .is_some();
+
+    let download_count = parse_field(
+        "download_count",
+        &nth_stat(&dom, 2, "download_count")?.replace("Downloads: ", ""),
+    )?;
+
+    let fav_count = parse_field(
+        "fav_count",
+        &nth_stat(&dom, 3, "fav_count")?
+            .replace("Favourited: ", "")
+            .replace(" times", ""),
+    )?;
+
+    let channel_count = parse_field(
+        "channel_count",
+        &nth_stat(&dom, 6, "channel_count")?.replace("Channels: ", ""),
+    )?;
+
+    let genre = decode_field("genre", &nth_stat(&dom, 8, "genre")?.replace("Genre: ", ""))?;
+
+    let upload_date_text: String = {
+        let stat = nth_stat(&dom, 0, "upload_date")?;
+        stat.split(" times since ")
+            .nth(1)
+            .ok_or_else(|| crate::Error::Parse {
+                field: "upload_date",
+                text: stat.clone(),
+            })?
+            .replace(" :D", "")
+            .trim()
+            .into()
+    };
+    let upload_date = parse_date("upload_date", &upload_date_text)?;
+
+    let instrument_text = {
+        decode_field(
+            "instrument_text",
+            &dom.query_selector("pre")
+                .and_then(|mut iter| iter.nth(1))
+                .and_then(|handle| handle.get(parser))
+                .ok_or(crate::Error::MissingField("instrument_text"))?
+                .inner_text(parser),
+        )?
+        .trim()
+        .into()
+    };
+
+    let artists = dom
+        .get_elements_by_class_name("mod-page-artist-info")
+        .next()
+        .and_then(|handle| handle.get(parser))
+        .and_then(|node| node.as_tag())
+        .map(|section| member_links(parser, section))
+        .transpose()?
+        .unwrap_or_default();
+
+    // unrated modules say so instead of giving a number, and the reviewer rating is only
+    // there for modules that got a review
+    let class_text = |class: &str| {
+        dom.get_elements_by_class_name(class)
+            .next()
+            .and_then(|handle| handle.get(parser))
+            .map(|node| node.inner_text(parser).into_owned())
+    };
+    let (member_rating, member_votes) = match class_text("mod-page-member-rating") {
+        Some(text) => {
+            let text = text.replace("Member Rating:", "");
+            let (rating, votes) = text.split_once('(').unwrap_or((&text, "0"));
+            let rating = match rating.trim() {
+                "Unrated" => None,
+                rating => Some(rating.parse::<Rating>()?),
+            };
+            (rating, first_number("member_votes", votes)?)
+        }
+        None => (None, 0),
+    };
+    let reviewer_rating = class_text("mod-page-reviewer-rating")
+        .map(|text| text.replace("Reviewer Rating:", "").parse::<Rating>())
+        .transpose()?;
+
+    // the license is a link when it has a page of its own, plain text otherwise
+    let license = dom
+        .get_elements_by_class_name("mod-page-license")
+        .next()
+        .and_then(|handle| handle.get(parser))
+        .and_then(|node| node.as_tag())
+        .map(|section| {
+            let link = section
+                .query_selector(parser, "a[href]")
+                .and_then(|mut iter| iter.next())
+                .and_then(|handle| handle.get(parser))
+                .and_then(|node| node.as_tag());
+            let (text, url) = match link {
+                Some(link) => (
+                    link.inner_text(parser),
+                    link.attributes()
+                        .get("href")
+                        .flatten()
+                        .map(|href| href.as_utf8_str()),
+                ),
+                None => (
+                    section
+                        .query_selector(parser, "p")
+                        .and_then(|mut iter| iter.next())
+                        .and_then(|handle| handle.get(parser))
+                        .map(|node| node.inner_text(parser))
+                        .unwrap_or_default(),
+                    None,
+                ),
+            };
+            Ok::<_, crate::Error>(License::from_page(
+                &decode_field("license", &text)?,
+                url.as_deref(),
+            ))
+        })
+        .transpose()?;
+
+    Ok(ModInfo {
+        id,
+        filename,
+        title,
+        size,
+        size_text,
+        md5,
+        format,
+        spotlit,
+        download_count,
+        fav_count,
+        scrape_time,
+        channel_count,
+        genre,
+        upload_date,
+        upload_date_text,
+        instrument_text,
+        artists,
+        member_rating,
+        member_votes,
+        reviewer_rating,
+        license,
+    })
+}
+
+/// Runs the extraction over the body of a module's reviews page.
+pub(crate) fn reviews(body: &str) -> Result<Vec<Review>, crate::Error> {
+    let dom = parse_dom(body)?;
+    let parser = dom.parser();
+
+    // the list is there even when it's empty, it's only missing when the module is
+    let list = dom
+        .get_elements_by_class_name("mod-reviews")
+        .next()
+        .and_then(|handle| handle.get(parser))
+        .and_then(|node| node.as_tag())
+        .ok_or(crate::Error::NotFound)?;
+
+    list.query_selector(parser, ".mod-review")
+        .into_iter()
+        .flatten()
+        .filter_map(|handle| handle.get(parser))
+        .filter_map(|node| node.as_tag())
+        .map(|review| {
+            let child_text = |selector: &str| {
+                review
+                    .query_selector(parser, selector)
+                    .and_then(|mut iter| iter.next())
+                    .and_then(|handle| handle.get(parser))
+                    .map(|node| node.inner_text(parser).trim().to_string())
+            };
+
+            let author = member_links(parser, review)?
+                .into_iter()
+                .next()
+                .ok_or(crate::Error::MissingField("author"))?;
+
+            let date_text =
+                child_text(".mod-review-date").ok_or(crate::Error::MissingField("date"))?;
+            let date = parse_date("date", &date_text)?;
+
+            let rating = child_text(".mod-review-rating")
+                .map(|rating| rating.parse())
+                .transpose()?;
+
+            let text = decode_field(
+                "text",
+                &child_text(".mod-review-text").ok_or(crate::Error::MissingField("text"))?,
+            )?;
+
+            Ok(Review {
+                author,
+                date,
+                date_text,
+                rating,
+                text,
+            })
+        })
+        .collect()
+}
+
+/// Runs the extraction over one page of a module's comments
+pub(crate) fn comments(body: &str) -> Result<CommentPage, crate::Error> {
+    let dom = parse_dom(body)?;
+    let parser = dom.parser();
+
+    let list = dom
+        .get_elements_by_class_name("mod-comments")
+        .next()
+        .and_then(|handle| handle.get(parser))
+        .and_then(|node| node.as_tag())
+        .ok_or(crate::Error::NotFound)?;
+
+    let comments = list
+        .query_selector(parser, ".mod-comment")
+        .into_iter()
+        .flatten()
+        .filter_map(|handle| handle.get(parser))
+        .filter_map(|node| node.as_tag())
+        .map(|comment| {
+            let child_text = |selector: &str| {
+                comment
+                    .query_selector(parser, selector)
+                    .and_then(|mut iter| iter.next())
+                    .and_then(|handle| handle.get(parser))
+                    .map(|node| node.inner_text(parser).trim().to_string())
+            };
+
+            let author = member_links(parser, comment)?
+                .into_iter()
+                .next()
+                .ok_or(crate::Error::MissingField("author"))?;
+
+            let time_text =
+                child_text(".mod-comment-date").ok_or(crate::Error::MissingField("time"))?;
+            let time = parse_timestamp("time", &time_text)?;
+
+            let text = decode_field(
+                "text",
+                &child_text(".mod-comment-text").ok_or(crate::Error::MissingField("text"))?,
+            )?;
+
+            Ok(Comment {
+                author,
+                time,
+                time_text,
+                text,
+            })
+        })
+        .collect::<Result<Vec<Comment>, crate::Error>>()?;
+
+    let (page, page_count) = page_position(&dom)?;
+
+    Ok(CommentPage {
+        comments,
+        page,
+        page_count,
+    })
+}
+
+/// The member ID in a profile link, they come as `member.php?69141` or as
+/// `index.php?request=view_profile&query=69141`
+fn member_id(href: &str) -> Option<u32> {
+    href.split("member.php?")
+        .nth(1)
+        .or_else(|| href.split("query=").nth(1))?
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Every link to a member profile inside `section`, the link text is the member's name
+fn member_links(parser: &tl::Parser, section: &tl::HTMLTag) -> Result<Vec<Member>, crate::Error> {
+    section
+        .query_selector(parser, "a[href]")
+        .into_iter()
+        .flatten()
+        .filter_map(|handle| handle.get(parser))
+        .filter_map(|node| node.as_tag())
+        .filter_map(|tag| {
+            let href = tag.attributes().get("href")??.as_utf8_str();
+            let id = member_id(&href)?;
+            Some((id, tag.inner_text(parser)))
+        })
+        .map(|(id, name)| {
+            Ok(Member {
+                id,
+                name: decode_field("artists", name.trim())?,
+            })
+        })
+        .collect()
+}
+
+/// Runs the extraction over the body of a member profile, the module IDs are left empty since
+/// they're on separate pages.
+pub(crate) fn artist(member_id: u32, body: &str) -> Result<Artist, crate::Error> {
+    let dom = parse_dom(body)?;
+    let parser = dom.parser();
+
+    let profile = dom
+        .get_elements_by_class_name("member-profile")
+        .next()
+        .and_then(|handle| handle.get(parser))
+        .and_then(|node| node.as_tag())
+        .ok_or(crate::Error::NotFound)?;
+
+    let name = decode_field(
+        "name",
+        dom.query_selector("h1")
+            .and_then(|mut iter| iter.next())
+            .and_then(|handle| handle.get(parser))
+            .ok_or(crate::Error::MissingField("name"))?
+            .inner_text(parser)
+            .trim(),
+    )?;
+
+    let join_date_text: String = profile
+        .query_selector(parser, "li.stats")
+        .into_iter()
+        .flatten()
+        .filter_map(|handle| handle.get(parser))
+        .map(|node| node.inner_text(parser))
+        .find_map(|stat| Some(stat.trim().strip_prefix("Joined:")?.trim().to_string()))
+        .ok_or(crate::Error::MissingField("join_date"))?;
+    let join_date = parse_date("join_date", &join_date_text)?;
+
+    // members without a bio don't get the section at all
+    let bio = match dom
+        .get_elements_by_class_name("member-profile-bio")
+        .next()
+        .and_then(|handle| handle.get(parser))
+        .and_then(|node| node.as_tag())
+        .and_then(|section| section.query_selector(parser, "pre")?.next())
+        .and_then(|handle| handle.get(parser))
+    {
+        Some(node) => decode_field("bio", &node.inner_text(parser))?.trim().into(),
+        None => String::new(),
+    };
+
+    Ok(Artist {
+        id: member_id,
+        name,
+        bio,
+        join_date,
+        join_date_text,
+        module_ids: Vec::new(),
+    })
+}
+
+/// Pulls the first run of digits out of `text`, commas included so "1,234" works too.
+fn first_number(field: &'static str, text: &str) -> Result<u32, crate::Error> {
+    let digits: String = text
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit() || *c == ',')
+        .filter(|c| *c != ',')
+        .collect();
+
+    digits.parse().map_err(|_| crate::Error::Parse {
+        field,
+        text: text.into(),
+    })
+}
+
+/// Where a paged listing is at as `(page, page_count)`, read from the "jump to page" dropdown
+/// that has one option per page with the current one selected. It's left out entirely when
+/// everything fits on a single page.
+fn page_position(dom: &tl::VDom) -> Result<(u32, u32), crate::Error> {
+    let parser = dom.parser();
+    let mut page = 1;
+    let mut page_count = 1;
+    let page_select = dom
+        .query_selector("select[name=page]")
+        .and_then(|mut iter| iter.next())
+        .and_then(|handle| handle.get(parser))
+        .and_then(|node| node.as_tag());
+
+    // tl doesn't do descendant combinators so the options are looked up from the select itself
+    for tag in page_select
+        .and_then(|select| select.query_selector(parser, "option"))
+        .into_iter()
+        .flatten()
+        .filter_map(|handle| handle.get(parser))
+        .filter_map(|node| node.as_tag())
+    {
+        let value = match tag.attributes().get("value") {
+            Some(Some(value)) => value.as_utf8_str().into_owned(),
+            _ => return Err(crate::Error::MissingField("page_count")),
+        };
+        let number = parse_field("page_count", &value)?;
+
+        page_count = page_count.max(number);
+        if tag.attributes().contains("selected") {
+            page = number;
+        }
+    }
+
+    Ok((page, page_count))
+}
+
+/// Runs the extraction over the body of a search results page.
+pub(crate) fn search_page(body: &str) -> Result<SearchPage, crate::Error> {
+    let dom = parse_dom(body)?;
+    let parser = dom.parser();
+
+    let status = dom
+        .query_selector("h1.site-wide-page-head-title")
+        .and_then(|mut iter| iter.next());
+
+    match status {
+        Some(_) => {}
+        None => return Err(crate::Error::NotFound),
+    };
+
+    let results = dom
+        .query_selector("a.standard-link[title]")
+        .into_iter()
+        .flatten()
+        .filter_map(|nodehandle| nodehandle.get(parser))
+        .filter_map(|node| node.as_tag())
+        .map(|tag| {
+            let href = match tag.attributes().get("href") {
+                Some(Some(href)) => href.as_utf8_str().into_owned(),
+                _ => return Err(crate::Error::MissingField("id")),
+            };
+
+            let id = parse_field("id", href.split("query=").nth(1).unwrap_or_default())?;
+
+            let filename = tag.inner_text(parser).into();
+
+            Ok(ModSearch { id, filename })
+        })
+        .collect::<Result<Vec<ModSearch>, crate::Error>>()?;
+
+    let (page, page_count) = page_position(&dom)?;
+
+    let result_count = match dom
+        .query_selector(".search-result-count")
+        .and_then(|mut iter| iter.next())
+        .and_then(|handle| handle.get(parser))
+    {
+        Some(node) => first_number("result_count", &node.inner_text(parser))?,
+        None if page_count == 1 => results.len() as u32,
+        None => return Err(crate::Error::MissingField("result_count")),
+    };
+
+    Ok(SearchPage {
+        results,
+        page,
+        page_count,
+        result_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_date, parse_size, parse_timestamp};
+    use chrono::prelude::{TimeZone, Utc};
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("size", "204.54 KB").unwrap(), 209449);
+        assert_eq!(parse_size("size", "1.95 MB").unwrap(), 2044723);
+        assert_eq!(parse_size("size", "812 B").unwrap(), 812);
+        assert!(parse_size("size", "a lot").is_err());
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(
+            parse_date("upload_date", "Thu 7th Dec 2000").unwrap(),
+            Utc.with_ymd_and_hms(2000, 12, 7, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_date("upload_date", "1st Mar 1999").unwrap(),
+            Utc.with_ymd_and_hms(1999, 3, 1, 0, 0, 0).unwrap()
+        );
+        assert!(parse_date("upload_date", "yesterday").is_err());
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(
+            parse_timestamp("time", "Wed 4th Apr 2001 21:07").unwrap(),
+            Utc.with_ymd_and_hms(2001, 4, 4, 21, 7, 0).unwrap()
+        );
+        assert!(parse_timestamp("time", "Wed 4th Apr 2001").is_err());
+        assert!(parse_timestamp("time", "Wed 4th Apr 2001 25:00").is_err());
+    }
+}
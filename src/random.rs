@@ -0,0 +1,188 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::search::Listing;
+use crate::{Genre, ModArchiveClient, ModInfo, ModuleFormat, SearchPage, Transport};
+
+/// Narrows down what [`ModInfo::random_matching()`] can land on
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RandomFilter {
+    /// Only modules in this format
+    Format(ModuleFormat),
+    /// Only modules in this genre
+    Genre(Genre),
+}
+
+impl RandomFilter {
+    /// The query string parameter for this filter, the same one the search form uses
+    pub(crate) fn param(&self) -> (&'static str, String) {
+        match self {
+            RandomFilter::Format(format) => ("format", format.to_string()),
+            RandomFilter::Genre(genre) => ("genre", genre.id().to_string()),
+        }
+    }
+
+    /// Whether `modinfo` is something this filter lets through
+    pub fn matches(&self, modinfo: &ModInfo) -> bool {
+        match self {
+            RandomFilter::Format(format) => modinfo.format == *format,
+            RandomFilter::Genre(genre) => modinfo.genre_kind() == Some(*genre),
+        }
+    }
+
+    /// The listing of every module this filter lets through, picked from when the random
+    /// module link doesn't go by the filter
+    pub(crate) fn listing(&self) -> Listing {
+        match self {
+            RandomFilter::Format(format) => Listing::Format(format.clone()),
+            RandomFilter::Genre(genre) => Listing::Genre(*genre),
+        }
+    }
+}
+
+/// A random number below `bound`, every [`RandomState`] is keyed differently which is plenty
+/// for picking a module without pulling in a crate for it
+fn random_below(bound: u32) -> u32 {
+    (RandomState::new().build_hasher().finish() % u64::from(bound.max(1))) as u32
+}
+
+/// Which page of a filtered listing to pick a module from, `first` being its first page
+pub(crate) fn pick_page(first: &SearchPage) -> u32 {
+    1 + random_below(first.page_count)
+}
+
+/// The ID of a random module on a page of a filtered listing, an empty listing means no
+/// module matches the filter at all
+pub(crate) fn pick_result(page: &SearchPage) -> Result<u32, crate::Error> {
+    if page.results.is_empty() {
+        return Err(crate::Error::NotFound);
+    }
+    Ok(page.results[random_below(page.results.len() as u32) as usize].id)
+}
+
+impl ModInfo {
+    /// Gets a random module from Mod Archive, following the site's random module link so
+    /// every call is one request that always lands on a module that exists.
+    ///
+    /// This goes through a default [`ModArchiveClient`], use [`ModArchiveClient::random_mod()`]
+    /// if you want to bring your own [`Transport`].
+    pub fn random() -> Result<ModInfo, crate::Error> {
+        ModArchiveClient::new().random_mod()
+    }
+
+    /// Same as [`ModInfo::random()`] but only lands on modules that match `filter`. The site
+    /// is asked for a matching module and the answer is checked too, if it doesn't match then
+    /// a random module is picked out of the filter's listing instead (see
+    /// [`ModInfo::format_page()`] and [`ModInfo::genre_page()`]), which costs a few more
+    /// requests. [`Error::NotFound`](crate::Error::NotFound) means no module matches at all.
+    pub fn random_matching(filter: &RandomFilter) -> Result<ModInfo, crate::Error> {
+        ModArchiveClient::new().random_mod_matching(filter)
+    }
+}
+
+impl<T: Transport> ModArchiveClient<T> {
+    /// Same as [`ModInfo::random()`] but through this client's transport, random modules never
+    /// come from the cache but they do get stored in it
+    pub fn random_mod(&self) -> Result<ModInfo, crate::Error> {
+        self.fetch_landing_mod(&self.urls().random(None))
+    }
+
+    /// Same as [`ModInfo::random_matching()`] but through this client's transport
+    pub fn random_mod_matching(&self, filter: &RandomFilter) -> Result<ModInfo, crate::Error> {
+        let modinfo = self.fetch_landing_mod(&self.urls().random(Some(filter)))?;
+        if filter.matches(&modinfo) {
+            return Ok(modinfo);
+        }
+
+        // the random link didn't go by the filter, pick out of the filtered listing instead
+        let listing = filter.listing();
+        let first = self.listing_page(&listing, 1)?;
+        let page = match pick_page(&first) {
+            1 => first,
+            page => self.listing_page(&listing, page)?,
+        };
+        self.get_mod(pick_result(&page)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{fixture_client, fixture_page, page_of};
+    use crate::{Error, Genre, ModuleFormat, RandomFilter, ResponseCache};
+
+    /// Stands in for the random module link, the redirect has already been followed so the
+    /// module page comes straight back. IT modules land on the spotlit fixture, everything
+    /// else on the XM one.
+    fn random_site(url: &str) -> Option<String> {
+        let body = if url.contains("query=54&search_type=genre") {
+            return Some(fixture_page(
+                include_str!("../tests/fixtures/browse_genre.html"),
+                page_of(url),
+                &[(61772, 61800), (61790, 61801)],
+            ));
+        } else if url.contains("query=MOD&search_type=format") {
+            include_str!("../tests/fixtures/search_empty.html")
+        } else if url.contains("request=view_by_moduleid") {
+            include_str!("../tests/fixtures/module.html")
+        } else if !url.contains("request=view_random") {
+            return None;
+        } else if url.contains("format=IT") {
+            include_str!("../tests/fixtures/module_spotlit.html")
+        } else {
+            include_str!("../tests/fixtures/module.html")
+        };
+        Some(body.to_string())
+    }
+
+    #[test]
+    fn random_module() {
+        let dir = std::env::temp_dir().join(format!("trackermeta-random-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let client = fixture_client(random_site).with_cache(ResponseCache::new(&dir));
+
+        // the ID comes from the page it landed on
+        let modinfo = client.random_mod().unwrap();
+        assert_eq!(modinfo.id, 61772);
+        assert_eq!(modinfo.format, ModuleFormat::Xm);
+
+        // and the page is cached as that module's page
+        let cached = client.get_mod(61772).unwrap();
+        assert_eq!(cached, modinfo);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn random_module_matching() {
+        let client = fixture_client(random_site);
+
+        let filter = RandomFilter::Format(ModuleFormat::It);
+        let modinfo = client.random_mod_matching(&filter).unwrap();
+        assert_eq!(modinfo.id, 158263);
+        assert!(filter.matches(&modinfo));
+
+        assert_eq!(
+            client
+                .random_mod_matching(&RandomFilter::Genre(Genre::TranceDream))
+                .unwrap()
+                .id,
+            61772
+        );
+
+        // the site ignoring the filter isn't taken at its word, the module comes out of the
+        // genre's listing instead
+        let modinfo = client
+            .random_mod_matching(&RandomFilter::Genre(Genre::Chiptune))
+            .unwrap();
+        assert!([61772, 61790, 61800, 61801].contains(&modinfo.id));
+
+        // and when that listing is empty nothing matches
+        assert_eq!(
+            client
+                .random_mod_matching(&RandomFilter::Format(ModuleFormat::Mod))
+                .unwrap_err(),
+            Error::NotFound
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// A token bucket rate limiter that keeps clients from hammering Mod Archive, by default it
+/// allows 1 request per second.
+///
+/// Clones share the same bucket so one limiter can be handed to as many clients and threads as
+/// you like and they'll all stay under the limit together. The free functions like
+/// [`ModInfo::get()`](crate::ModInfo::get) and every client made with
+/// [`ModArchiveClient::new()`](crate::ModArchiveClient::new) share one process-wide limiter,
+/// clients made with a custom transport start out with a limiter of their own.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use trackermeta::{ModArchiveClient, RateLimiter};
+///
+/// // bursts of up to 5 requests, refilling at 5 requests every 10 seconds
+/// let limiter = RateLimiter::new(5, Duration::from_secs(10));
+/// let client = ModArchiveClient::new().with_rate_limiter(limiter.clone());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Option<Arc<Mutex<Bucket>>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    /// Time it takes for one token to come back
+    refill: Duration,
+    /// Can go negative, that's how many requests are already waiting on a token
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    /// Allows `requests` requests every `per`, which is also the biggest burst allowed
+    pub fn new(requests: u32, per: Duration) -> Self {
+        let requests = requests.max(1);
+        RateLimiter {
+            bucket: Some(Arc::new(Mutex::new(Bucket {
+                capacity: requests as f64,
+                refill: per / requests,
+                tokens: requests as f64,
+                last: Instant::now(),
+            }))),
+        }
+    }
+
+    /// A limiter that never makes anyone wait, only use this against your own mirror or a
+    /// local stand-in server
+    pub fn unlimited() -> Self {
+        RateLimiter { bucket: None }
+    }
+
+    /// Takes a token out of the bucket and returns how long the caller has to wait before
+    /// making its request, the token is taken either way so callers must actually wait.
+    pub fn reserve(&self) -> Duration {
+        let Some(bucket) = &self.bucket else {
+            return Duration::ZERO;
+        };
+        let mut bucket = bucket
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let now = Instant::now();
+        let refilled = now.duration_since(bucket.last).as_secs_f64() / bucket.refill.as_secs_f64();
+        bucket.tokens = (bucket.tokens + refilled).min(bucket.capacity) - 1.0;
+        bucket.last = now;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            bucket.refill.mul_f64(-bucket.tokens)
+        }
+    }
+
+    /// Blocks the current thread until a request is allowed
+    pub fn wait(&self) {
+        let delay = self.reserve();
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+    }
+
+    /// The process-wide limiter shared by the free functions and the default clients
+    pub(crate) fn shared() -> RateLimiter {
+        static SHARED: OnceLock<RateLimiter> = OnceLock::new();
+        SHARED.get_or_init(RateLimiter::default).clone()
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        RateLimiter::new(1, Duration::from_secs(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RateLimiter;
+    use std::time::Duration;
+
+    #[test]
+    fn bucket_drains_and_queues() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(20));
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+
+        // clones share the bucket, and every waiting request queues behind the last one
+        let shared = limiter.clone();
+        let third = shared.reserve();
+        let fourth = limiter.reserve();
+        assert!(third > Duration::from_secs(9) && third <= Duration::from_secs(10));
+        assert!(fourth > Duration::from_secs(19) && fourth <= Duration::from_secs(20));
+    }
+
+    #[test]
+    fn unlimited_never_waits() {
+        let limiter = RateLimiter::unlimited();
+        for _ in 0..100 {
+            assert_eq!(limiter.reserve(), Duration::ZERO);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How a client retries requests that failed, used for every request it makes (module pages,
+/// searches and the rest).
+///
+/// The delay between attempts starts at the initial backoff and doubles every time up to the
+/// maximum, with jitter enabled each delay is randomly picked between half and all of that so
+/// a bunch of clients don't all retry at once.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use trackermeta::{ModArchiveClient, RetryPolicy};
+///
+/// let client = ModArchiveClient::new().with_retry_policy(
+///     RetryPolicy::new()
+///         .max_attempts(5)
+///         .backoff(Duration::from_secs(1), Duration::from_secs(60)),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: Option<u32>,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    retry_if: fn(&crate::Error) -> bool,
+}
+
+impl RetryPolicy {
+    /// The default policy, 3 attempts with a backoff going from 500ms up to 30s and jitter,
+    /// only retrying errors where [`Error::is_retryable()`](crate::Error::is_retryable) is true.
+    ///
+    /// With the deprecated `infinity-retry` feature the number of attempts is unlimited.
+    pub fn new() -> Self {
+        RetryPolicy {
+            max_attempts: if cfg!(feature = "infinity-retry") {
+                None
+            } else {
+                Some(3)
+            },
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: true,
+            retry_if: crate::Error::is_retryable,
+        }
+    }
+
+    /// A policy that never retries, the first error is returned as-is
+    pub fn none() -> Self {
+        RetryPolicy::new().max_attempts(1)
+    }
+
+    /// How many times a request is tried in total, counting the first one, `0` is treated as 1
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts.max(1));
+        self
+    }
+
+    /// Keep on retrying for as long as the errors are retryable, this is what the
+    /// `infinity-retry` feature used to do (minus hammering the site with no delay)
+    pub fn unlimited_attempts(mut self) -> Self {
+        self.max_attempts = None;
+        self
+    }
+
+    /// The delay before the first retry and the cap it's allowed to grow to
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Whether the delays get randomized, on by default
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Replaces the rule deciding which errors are worth retrying
+    pub fn retry_if(mut self, retry_if: fn(&crate::Error) -> bool) -> Self {
+        self.retry_if = retry_if;
+        self
+    }
+
+    /// Whether a request that failed with `err` on its `attempt`th try (starting from 1) should
+    /// be tried again
+    pub fn should_retry(&self, err: &crate::Error, attempt: u32) -> bool {
+        let attempts_left = match self.max_attempts {
+            Some(max_attempts) => attempt < max_attempts,
+            None => true,
+        };
+
+        attempts_left && (self.retry_if)(err)
+    }
+
+    /// How long to wait after the `attempt`th try (starting from 1) before trying again
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+
+        if !self.jitter || backoff.is_zero() {
+            return backoff;
+        }
+
+        // RandomState is seeded randomly for every instance, good enough for jitter without
+        // pulling in a whole rng crate
+        let random = RandomState::new().build_hasher().finish();
+        let half = backoff / 2;
+        half + Duration::from_nanos(random % (half.as_nanos() as u64 + 1))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, ModArchiveClient, RateLimiter, Response, RetryPolicy, Transport};
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    /// Answers with `status` for the first `failures` requests and an empty search after that
+    struct FlakyTransport {
+        status: u16,
+        failures: u32,
+        calls: Cell<u32>,
+    }
+
+    impl Transport for FlakyTransport {
+        fn fetch(&self, _url: &str) -> Result<Response, Error> {
+            self.calls.set(self.calls.get() + 1);
+            if self.calls.get() <= self.failures {
+                return Ok(Response::new(self.status, ""));
+            }
+            Ok(Response::new(
+                200,
+                include_str!("../tests/fixtures/search_empty.html"),
+            ))
+        }
+    }
+
+    fn client(status: u16, failures: u32, policy: RetryPolicy) -> ModArchiveClient<FlakyTransport> {
+        ModArchiveClient::with_transport(FlakyTransport {
+            status,
+            failures,
+            calls: Cell::new(0),
+        })
+        .with_retry_policy(policy.backoff(Duration::ZERO, Duration::ZERO))
+        .with_rate_limiter(RateLimiter::unlimited())
+    }
+
+    #[test]
+    fn retries_server_errors() {
+        let client = client(503, 2, RetryPolicy::new().max_attempts(3));
+        assert!(client.resolve_filename("a.mod").is_ok());
+        assert_eq!(client.transport().calls.get(), 3);
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let client = client(500, 5, RetryPolicy::new().max_attempts(2));
+        assert_eq!(
+            client.resolve_filename("a.mod").unwrap_err(),
+            Error::Status(500)
+        );
+        assert_eq!(client.transport().calls.get(), 2);
+    }
+
+    #[test]
+    fn does_not_retry_client_errors() {
+        let client = client(404, 1, RetryPolicy::new().unlimited_attempts());
+        assert_eq!(
+            client.resolve_filename("a.mod").unwrap_err(),
+            Error::Status(404)
+        );
+        assert_eq!(client.transport().calls.get(), 1);
+    }
+
+    #[test]
+    fn backoff_grows_and_caps() {
+        let policy = RetryPolicy::new()
+            .jitter(false)
+            .backoff(Duration::from_secs(1), Duration::from_secs(5));
+        assert_eq!(policy.delay(1), Duration::from_secs(1));
+        assert_eq!(policy.delay(2), Duration::from_secs(2));
+        assert_eq!(policy.delay(3), Duration::from_secs(4));
+        assert_eq!(policy.delay(4), Duration::from_secs(5));
+        assert_eq!(policy.delay(100), Duration::from_secs(5));
+
+        let jittered = policy.jitter(true).delay(3);
+        assert!(jittered >= Duration::from_secs(2) && jittered <= Duration::from_secs(4));
+    }
+}
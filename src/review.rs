@@ -0,0 +1,84 @@
+use chrono::prelude::{DateTime, Utc};
+
+use crate::{parse, Member, ModArchiveClient, ModInfo, Rating, Transport};
+
+/// A written review of a module, get them with [`ModInfo::reviews()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Review {
+    /// The member who wrote the review
+    pub author: Member,
+    /// When the review was written, the site only gives the day so this is always midnight
+    pub date: DateTime<Utc>,
+    /// The date as the site shows it, for example `Tue 3rd Apr 2001`
+    pub date_text: String,
+    /// The score the reviewer gave, not every review has one
+    pub rating: Option<Rating>,
+    /// The review itself
+    pub text: String,
+}
+
+impl Review {
+    /// Does the same extraction as [`ModInfo::reviews()`] but on a reviews page you already
+    /// have, nothing is fetched
+    pub fn from_html(html: &str) -> Result<Vec<Review>, crate::Error> {
+        parse::reviews(html)
+    }
+}
+
+impl ModInfo {
+    /// Gets the written reviews of the module with ID `mod_id`, oldest first, a module nobody
+    /// reviewed just has none. The scores are summed up in [`ModInfo::reviewer_rating`].
+    ///
+    /// This goes through a default [`ModArchiveClient`], use [`ModArchiveClient::get_reviews()`]
+    /// if you want to bring your own [`Transport`].
+    pub fn reviews(mod_id: u32) -> Result<Vec<Review>, crate::Error> {
+        ModArchiveClient::new().get_reviews(mod_id)
+    }
+}
+
+impl<T: Transport> ModArchiveClient<T> {
+    /// Same as [`ModInfo::reviews()`] but through this client's transport
+    pub fn get_reviews(&self, mod_id: u32) -> Result<Vec<Review>, crate::Error> {
+        Review::from_html(&self.fetch_page(&self.urls().reviews(mod_id))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, Member, Rating, Review};
+    use chrono::prelude::{TimeZone, Utc};
+
+    #[test]
+    fn fixture_reviews() {
+        let reviews =
+            Review::from_html(include_str!("../tests/fixtures/module_reviews.html")).unwrap();
+        assert_eq!(reviews.len(), 2);
+
+        assert_eq!(
+            reviews[0].author,
+            Member {
+                id: 1234,
+                name: "Sapphire".into()
+            }
+        );
+        assert_eq!(
+            reviews[0].date,
+            Utc.with_ymd_and_hms(2001, 4, 3, 0, 0, 0).unwrap()
+        );
+        assert_eq!(reviews[0].rating, Some(Rating::from_hundredths(800)));
+        assert_eq!(
+            reviews[0].text,
+            "Lush pads & a driving beat, the breakdown\naround the two minute mark is a real treat."
+        );
+
+        assert_eq!(reviews[1].author.id, 5678);
+        assert_eq!(reviews[1].date_text, "Fri 9th Aug 2002");
+        assert_eq!(reviews[1].rating, None);
+
+        assert_eq!(
+            Review::from_html(include_str!("../tests/fixtures/module_not_found.html")).unwrap_err(),
+            Error::NotFound
+        );
+    }
+}
@@ -0,0 +1,513 @@
+use crate::{parse, Genre, ModArchiveClient, ModInfo, ModSearch, ModuleFormat, Transport};
+
+/// What a [`SearchQuery`] is matched against on Mod Archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SearchType {
+    /// The filename of the module, this is what
+    /// [`ModInfo::resolve_filename()`](crate::ModInfo::resolve_filename) uses
+    #[default]
+    Filename,
+    /// The song title stored inside the module
+    Title,
+    /// Either the filename or the song title
+    FilenameOrTitle,
+    /// The instrument (and sample) text of the module
+    InstrumentText,
+    /// The comments/message of the module
+    Comments,
+    /// The name of the artist the module is credited to
+    Artist,
+    /// The MD5 hash of the module file, as 32 hex characters
+    Hash,
+}
+
+impl SearchType {
+    /// The value Mod Archive's search form uses for this search type
+    pub fn as_param(&self) -> &'static str {
+        match self {
+            SearchType::Filename => "filename",
+            SearchType::Title => "songtitle",
+            SearchType::FilenameOrTitle => "filename_or_songtitle",
+            SearchType::InstrumentText => "module_instruments",
+            SearchType::Comments => "module_comments",
+            SearchType::Artist => "search_artist",
+            SearchType::Hash => "hash",
+        }
+    }
+}
+
+/// A builder for the full Mod Archive search, with the same filters the advanced search form
+/// has. Hand it to [`ModArchiveClient::search()`] or [`ModInfo::search()`](crate::ModInfo::search).
+///
+/// ```rust
+/// use trackermeta::{ModInfo, ModuleFormat, SearchQuery, SearchType};
+///
+/// let query = SearchQuery::new("dance")
+///     .search_type(SearchType::Title)
+///     .format(ModuleFormat::Xm)
+///     .channels(8, 16);
+/// let page = ModInfo::search(&query).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchQuery {
+    query: String,
+    search_type: SearchType,
+    format: Option<ModuleFormat>,
+    genre: Option<u32>,
+    channels: Option<(u32, u32)>,
+    size_kb: Option<(u32, u32)>,
+    page: u32,
+}
+
+impl SearchQuery {
+    /// Starts a [`SearchType::Filename`] search for `query` with no filters, on the first page
+    pub fn new(query: impl Into<String>) -> Self {
+        SearchQuery {
+            query: query.into(),
+            search_type: SearchType::default(),
+            format: None,
+            genre: None,
+            channels: None,
+            size_kb: None,
+            page: 1,
+        }
+    }
+
+    /// What the query is matched against
+    pub fn search_type(mut self, search_type: SearchType) -> Self {
+        self.search_type = search_type;
+        self
+    }
+
+    /// Only return modules in this format
+    pub fn format(mut self, format: ModuleFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Only return modules in this genre, takes a [`Genre`] or a Mod Archive genre ID
+    pub fn genre(mut self, genre: impl Into<u32>) -> Self {
+        self.genre = Some(genre.into());
+        self
+    }
+
+    /// Only return modules with a channel count between `min` and `max` (inclusive)
+    pub fn channels(mut self, min: u32, max: u32) -> Self {
+        self.channels = Some((min, max));
+        self
+    }
+
+    /// Only return modules with a file size between `min` and `max` kilobytes (inclusive)
+    pub fn size_kb(mut self, min: u32, max: u32) -> Self {
+        self.size_kb = Some((min, max));
+        self
+    }
+
+    /// Which page of results to get, starting from 1
+    pub fn page(mut self, page: u32) -> Self {
+        self.page = page;
+        self
+    }
+
+    /// The page of results this query is for
+    pub fn page_number(&self) -> u32 {
+        self.page
+    }
+
+    /// The query string parameters for this search, in the order the site's form sends them
+    pub(crate) fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("request", "search".into()),
+            ("query", self.query.clone()),
+            ("submit", "Find".into()),
+            ("search_type", self.search_type.as_param().into()),
+        ];
+
+        if let Some(format) = &self.format {
+            params.push(("format", format.to_string()));
+        }
+        if let Some(genre) = self.genre {
+            params.push(("genre", genre.to_string()));
+        }
+        if let Some((min, max)) = self.channels {
+            params.push(("channels", format!("{}-{}", min, max)));
+        }
+        if let Some((min, max)) = self.size_kb {
+            params.push(("size", format!("{}-{}", min, max)));
+        }
+        params.push(("page", self.page.to_string()));
+
+        params
+    }
+}
+
+/// Anything Mod Archive pages through the same way as its search results, a list of module
+/// links with a "jump to page" dropdown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Listing {
+    Search(SearchQuery),
+    /// The modules credited to the artist with this member ID
+    ArtistModules(u32),
+    /// Every module in a genre
+    Genre(Genre),
+    /// Every module in a format
+    Format(ModuleFormat),
+    /// Every module uploaded in a year, or in one month of it
+    Uploaded {
+        year: i32,
+        month: Option<u32>,
+    },
+}
+
+impl Listing {
+    /// Catches listings the site can't serve before anything is sent, like a month past 12
+    pub(crate) fn check(&self) -> Result<(), crate::Error> {
+        match self {
+            Listing::Uploaded {
+                month: Some(month), ..
+            } if !(1..=12).contains(month) => Err(crate::Error::InvalidArgument {
+                name: "month",
+                value: month.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// The query string parameters for the given page of this listing
+    pub(crate) fn params(&self, page: u32) -> Vec<(&'static str, String)> {
+        match self {
+            Listing::Search(query) => query.clone().page(page).params(),
+            Listing::ArtistModules(member_id) => vec![
+                ("request", "view_artist_modules".into()),
+                ("query", member_id.to_string()),
+                ("page", page.to_string()),
+            ],
+            Listing::Genre(genre) => vec![
+                ("request", "search".into()),
+                ("query", genre.id().to_string()),
+                ("search_type", "genre".into()),
+                ("page", page.to_string()),
+            ],
+            Listing::Format(format) => vec![
+                ("request", "search".into()),
+                ("query", format.to_string()),
+                ("search_type", "format".into()),
+                ("page", page.to_string()),
+            ],
+            Listing::Uploaded { year, month } => {
+                let mut params = vec![
+                    ("request", "view_by_date".into()),
+                    ("year", year.to_string()),
+                ];
+                if let Some(month) = month {
+                    params.push(("month", month.to_string()));
+                }
+                params.push(("page", page.to_string()));
+                params
+            }
+        }
+    }
+}
+
+/// A single page of search results along with where it sits in the whole search, Mod Archive
+/// hands out up to 40 results per page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchPage {
+    /// The results on this page
+    pub results: Vec<ModSearch>,
+    /// The number of this page, starting from 1
+    pub page: u32,
+    /// How many pages the whole search has
+    pub page_count: u32,
+    /// How many results the whole search has, across all pages
+    pub result_count: u32,
+}
+
+impl SearchPage {
+    /// Parses a search results page you already have, nothing is fetched
+    pub fn from_html(html: &str) -> Result<SearchPage, crate::Error> {
+        parse::search_page(html)
+    }
+
+    /// Whether there are no pages after this one
+    pub fn is_last(&self) -> bool {
+        self.page >= self.page_count
+    }
+}
+
+/// Where a walk through a paged listing goes after asking for one page, everything that pages
+/// through the site decides this here so none of them can get stuck on the same page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PageStep {
+    /// The site answered with a different page than the one asked for (it clamps page numbers
+    /// past the end), the answer is a repeat so it's dropped and the walk ends
+    Stale,
+    /// The page is the last one
+    Last,
+    /// Carry on with this page
+    Next(u32),
+}
+
+impl PageStep {
+    /// The step after asking for page `requested` and getting back page `page` of `page_count`
+    fn after(requested: u32, page: u32, page_count: u32) -> Self {
+        if page != requested {
+            PageStep::Stale
+        } else if requested >= page_count {
+            PageStep::Last
+        } else {
+            PageStep::Next(requested + 1)
+        }
+    }
+
+    /// Whether the page that was just fetched belongs in the results
+    fn keeps_page(self) -> bool {
+        self != PageStep::Stale
+    }
+
+    /// The page to fetch next, if there is one
+    fn next_page(self) -> Option<u32> {
+        match self {
+            PageStep::Next(page) => Some(page),
+            PageStep::Stale | PageStep::Last => None,
+        }
+    }
+}
+
+/// A walk through a paged listing that leaves the fetching to whoever drives it, the blocking
+/// and the async client both page through the site with one of these.
+///
+/// ```text
+/// while let Some(page) = walk.next_page() {
+///     let fetched = fetch(page)?;
+///     if walk.record(fetched.page, fetched.page_count) {
+///         keep(fetched);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PageWalk {
+    requested: u32,
+    next: Option<u32>,
+}
+
+impl PageWalk {
+    /// A walk starting at `first_page`
+    pub(crate) fn new(first_page: u32) -> Self {
+        PageWalk {
+            requested: first_page,
+            next: Some(first_page),
+        }
+    }
+
+    /// The page to fetch, `None` once the walk is over. A page that fails to load ends the
+    /// walk too since it's never recorded.
+    pub(crate) fn next_page(&mut self) -> Option<u32> {
+        self.requested = self.next.take()?;
+        Some(self.requested)
+    }
+
+    /// Records that the page just fetched came back as page `page` of `page_count`, returns
+    /// whether its results belong in the listing
+    pub(crate) fn record(&mut self, page: u32, page_count: u32) -> bool {
+        let step = PageStep::after(self.requested, page, page_count);
+        self.next = step.next_page();
+        step.keeps_page()
+    }
+}
+
+/// An iterator that goes through every page of a search (or a listing like an artist's
+/// modules) one request at a time, get one using [`ModArchiveClient::search_pages()`] or
+/// [`ModArchiveClient::resolve_filename_pages()`].
+///
+/// If a page fails to load the error is yielded and the iteration stops there.
+#[derive(Debug)]
+pub struct SearchPages<'a, T> {
+    client: &'a ModArchiveClient<T>,
+    listing: Listing,
+    walk: PageWalk,
+}
+
+impl<'a, T> SearchPages<'a, T> {
+    pub(crate) fn new(client: &'a ModArchiveClient<T>, listing: Listing, first_page: u32) -> Self {
+        SearchPages {
+            client,
+            listing,
+            walk: PageWalk::new(first_page),
+        }
+    }
+}
+
+impl<T: Transport> Iterator for SearchPages<'_, T> {
+    type Item = Result<SearchPage, crate::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let page = self.walk.next_page()?;
+        let result = self.client.listing_page(&self.listing, page);
+
+        if let Ok(search_page) = &result {
+            if !self.walk.record(search_page.page, search_page.page_count) {
+                return None;
+            }
+        }
+
+        Some(result)
+    }
+}
+
+impl<'a, T: Transport> SearchPages<'a, T> {
+    /// Turns the pages into an iterator over the full [`ModInfo`] of every result, fetching
+    /// each module page as it goes. That's one request per module on top of one per page, so
+    /// narrow things down with the listing or search first.
+    pub fn mod_infos(self) -> ModInfos<'a, T> {
+        ModInfos {
+            pages: self,
+            pending: Vec::new().into_iter(),
+        }
+    }
+}
+
+/// An iterator over the full [`ModInfo`] of every result in a search or listing, get one
+/// using [`SearchPages::mod_infos()`].
+///
+/// If a page or a module fails to load the error is yielded, a failed module is skipped over
+/// while a failed page stops the iteration there.
+///
+/// ```rust
+/// use chrono::Datelike;
+/// use trackermeta::{ModArchiveClient, ModuleFormat};
+///
+/// let client = ModArchiveClient::new();
+/// let corpus: Vec<_> = client
+///     .format_pages(ModuleFormat::It)
+///     .mod_infos()
+///     .filter_mods(|modinfo| modinfo.channel_count > 32 && modinfo.upload_date.year() == 2005)
+///     .take(10)
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ModInfos<'a, T> {
+    pages: SearchPages<'a, T>,
+    pending: std::vec::IntoIter<ModSearch>,
+}
+
+impl<'a, T: Transport> ModInfos<'a, T> {
+    /// Only keeps the modules `predicate` returns true for, errors are always passed through
+    pub fn filter_mods<F>(
+        self,
+        mut predicate: F,
+    ) -> impl Iterator<Item = Result<ModInfo, crate::Error>> + 'a
+    where
+        F: FnMut(&ModInfo) -> bool + 'a,
+    {
+        self.filter(move |modinfo| modinfo.as_ref().map_or(true, &mut predicate))
+    }
+}
+
+impl<T: Transport> Iterator for ModInfos<'_, T> {
+    type Item = Result<ModInfo, crate::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(result) = self.pending.next() {
+                return Some(self.pages.client.get_mod(result.id));
+            }
+            match self.pages.next()? {
+                Ok(page) => self.pending = page.results.into_iter(),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PageStep, PageWalk};
+    use crate::test_support::{fixture_client, fixture_page, page_of};
+    use crate::{ModuleFormat, SearchPage, SearchQuery, SearchType};
+
+    const SEARCH: &str = include_str!("../tests/fixtures/search.html");
+    const SEARCH_PAGED: &str = include_str!("../tests/fixtures/search_paged.html");
+
+    /// Serves the paged fixture with the selected option moved to whatever page was asked for
+    fn paged_search(url: &str) -> Option<String> {
+        Some(fixture_page(SEARCH_PAGED, page_of(url), &[]))
+    }
+
+    #[test]
+    fn single_page() {
+        let page = SearchPage::from_html(SEARCH).unwrap();
+        assert_eq!(page.page, 1);
+        assert_eq!(page.page_count, 1);
+        assert_eq!(page.result_count, 3);
+        assert!(page.is_last());
+    }
+
+    #[test]
+    fn paged() {
+        let page = SearchPage::from_html(SEARCH_PAGED).unwrap();
+        assert_eq!(page.page, 2);
+        assert_eq!(page.page_count, 3);
+        assert_eq!(page.result_count, 87);
+        assert_eq!(page.results.len(), 2);
+        assert!(!page.is_last());
+    }
+
+    #[test]
+    fn follows_every_page() {
+        let client = fixture_client(paged_search);
+        let pages: Vec<u32> = client
+            .resolve_filename_pages("intro.mod")
+            .map(|page| page.unwrap().page)
+            .collect();
+        assert_eq!(pages, [1, 2, 3]);
+    }
+
+    #[test]
+    fn stops_when_the_page_does_not_move() {
+        // the site ignores the page number and always answers with the first page
+        let client = fixture_client(|_| Some(fixture_page(SEARCH_PAGED, 1, &[])));
+        let pages: Vec<u32> = client
+            .resolve_filename_pages("intro.mod")
+            .map(|page| page.unwrap().page)
+            .collect();
+        assert_eq!(pages, [1]);
+
+        assert_eq!(PageStep::after(2, 2, 3), PageStep::Next(3));
+        assert_eq!(PageStep::after(3, 3, 3), PageStep::Last);
+        assert_eq!(PageStep::after(4, 3, 3), PageStep::Stale);
+
+        let mut walk = PageWalk::new(2);
+        assert_eq!(walk.next_page(), Some(2));
+        assert!(walk.record(2, 3));
+        assert_eq!(walk.next_page(), Some(3));
+        assert!(!walk.record(2, 3));
+        assert_eq!(walk.next_page(), None);
+    }
+
+    #[test]
+    fn query_params() {
+        let query = SearchQuery::new("dance")
+            .search_type(SearchType::InstrumentText)
+            .format(ModuleFormat::It)
+            .channels(32, 64)
+            .page(3);
+        assert_eq!(
+            query.params(),
+            [
+                ("request", "search".to_string()),
+                ("query", "dance".into()),
+                ("submit", "Find".into()),
+                ("search_type", "module_instruments".into()),
+                ("format", "IT".into()),
+                ("channels", "32-64".into()),
+                ("page", "3".into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Fakes shared by the unit tests, everything here answers from the fixtures in
+//! `tests/fixtures` and nothing talks to the network
+
+#[cfg(feature = "async")]
+use crate::nonblocking::{AsyncModArchiveClient, AsyncTransport};
+use crate::{Error, ModArchiveClient, RateLimiter, Response, Transport};
+
+/// Turns a fixture of the first page of a paged listing into page `page` of it, the "jump to
+/// page" dropdown gets that page selected and past the first page every `query=from` link in
+/// `renames` becomes `query=to` so the pages hold different results
+pub(crate) fn fixture_page(html: &str, page: u32, renames: &[(u32, u32)]) -> String {
+    let mut html = html
+        .replace(" selected=\"selected\"", "")
+        .replace(" selected>", ">")
+        .replace(
+            &format!("<option value=\"{}\">", page),
+            &format!("<option value=\"{}\" selected=\"selected\">", page),
+        );
+    if page > 1 {
+        for (from, to) in renames {
+            html = html.replace(&format!("query={}\"", from), &format!("query={}\"", to));
+        }
+    }
+    html
+}
+
+/// The page a listing URL asks for, the first one if it doesn't say
+pub(crate) fn page_of(url: &str) -> u32 {
+    url::Url::parse(url)
+        .unwrap()
+        .query_pairs()
+        .find(|(key, _)| key == "page")
+        .map_or(1, |(_, page)| page.parse().unwrap())
+}
+
+/// Serves both pages of comments on a module, the second page is the first one with different
+/// authors
+pub(crate) fn comment_pages(url: &str) -> Option<String> {
+    Some(fixture_page(
+        include_str!("../tests/fixtures/module_comments.html"),
+        page_of(url),
+        &[(1234, 3456), (9012, 7890)],
+    ))
+}
+
+/// Serves an artist's profile and both pages of their modules, the second page is the first
+/// one with different IDs
+pub(crate) fn artist_site(url: &str) -> Option<String> {
+    Some(if url.contains("request=view_profile") {
+        include_str!("../tests/fixtures/artist.html").to_string()
+    } else {
+        fixture_page(
+            include_str!("../tests/fixtures/artist_modules.html"),
+            page_of(url),
+            &[(61772, 61800), (61790, 61801)],
+        )
+    })
+}
+
+/// A transport that answers every request with the body `route` gives back for its URL, or a
+/// 404 when it gives back nothing
+pub(crate) struct FixtureTransport<F>(pub(crate) F);
+
+impl<F: Fn(&str) -> Option<String>> Transport for FixtureTransport<F> {
+    fn fetch(&self, url: &str) -> Result<Response, Error> {
+        Ok(match (self.0)(url) {
+            Some(body) => Response::new(200, body),
+            None => Response::new(404, ""),
+        })
+    }
+}
+
+/// A client answering from `route` through a [`FixtureTransport`], with no rate limit so the
+/// tests don't wait between requests
+pub(crate) fn fixture_client<F>(route: F) -> ModArchiveClient<FixtureTransport<F>>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    ModArchiveClient::with_transport(FixtureTransport(route))
+        .with_rate_limiter(RateLimiter::unlimited())
+}
+
+#[cfg(feature = "async")]
+impl<F: Fn(&str) -> Option<String>> AsyncTransport for FixtureTransport<F> {
+    fn fetch(
+        &self,
+        url: &str,
+    ) -> impl std::future::Future<Output = Result<Response, Error>> + Send {
+        std::future::ready(Transport::fetch(self, url))
+    }
+}
+
+/// The async counterpart of [`fixture_client()`]
+#[cfg(feature = "async")]
+pub(crate) fn fixture_async_client<F>(route: F) -> AsyncModArchiveClient<FixtureTransport<F>>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    AsyncModArchiveClient::with_transport(FixtureTransport(route))
+        .with_rate_limiter(RateLimiter::unlimited())
+}
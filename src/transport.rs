@@ -0,0 +1,164 @@
+use std::io::{Read, Write};
+
+/// A raw response as handed back by a [`Transport`], just the status code and the body bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Response {
+    /// The HTTP status code of the response
+    pub status: u16,
+    /// The undecoded body of the response
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Response {
+            status,
+            body: body.into(),
+        }
+    }
+
+    /// Decodes the body as UTF-8, which is what Mod Archive serves its pages in
+    pub fn text(&self) -> Result<String, crate::Error> {
+        String::from_utf8(self.body.clone()).map_err(|err| crate::Error::Decode(err.to_string()))
+    }
+}
+
+/// The HTTP backend used to talk to Mod Archive, implement this if you want to route requests
+/// through your own client (a proxy, extra headers, a fake for tests and so on).
+///
+/// Implementations should only return an [`Err`] when no response came back at all, a response
+/// with a non-success status is still an [`Ok`] and the caller decides what to do with it.
+pub trait Transport {
+    /// Does a `GET` request to `url` and returns whatever came back
+    fn fetch(&self, url: &str) -> Result<Response, crate::Error>;
+
+    /// Does a `GET` request to `url` and streams the body into `out` instead of holding on to
+    /// it, used for downloading modules. Returns the status, the body is only written for
+    /// success statuses.
+    ///
+    /// The default implementation goes through [`fetch()`](Transport::fetch) and writes out
+    /// the whole body at once.
+    fn fetch_to(&self, url: &str, out: &mut dyn Write) -> Result<u16, crate::Error> {
+        let response = self.fetch(url)?;
+        if (200..300).contains(&response.status) {
+            out.write_all(&response.body)?;
+        }
+        Ok(response.status)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for &T {
+    fn fetch(&self, url: &str) -> Result<Response, crate::Error> {
+        (**self).fetch(url)
+    }
+
+    fn fetch_to(&self, url: &str, out: &mut dyn Write) -> Result<u16, crate::Error> {
+        (**self).fetch_to(url, out)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn fetch(&self, url: &str) -> Result<Response, crate::Error> {
+        (**self).fetch(url)
+    }
+
+    fn fetch_to(&self, url: &str, out: &mut dyn Write) -> Result<u16, crate::Error> {
+        (**self).fetch_to(url, out)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for std::sync::Arc<T> {
+    fn fetch(&self, url: &str) -> Result<Response, crate::Error> {
+        (**self).fetch(url)
+    }
+
+    fn fetch_to(&self, url: &str, out: &mut dyn Write) -> Result<u16, crate::Error> {
+        (**self).fetch_to(url, out)
+    }
+}
+
+/// The default [`Transport`], a thin wrapper over a [`ureq::Agent`]
+#[derive(Debug, Clone)]
+pub struct UreqTransport {
+    agent: ureq::Agent,
+    headers: Vec<(String, String)>,
+}
+
+impl UreqTransport {
+    /// Wraps an already configured agent
+    pub fn new(agent: ureq::Agent) -> Self {
+        UreqTransport {
+            agent,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Adds a header that gets sent with every request, `ureq` agents can't hold on to
+    /// headers themselves
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+impl Default for UreqTransport {
+    /// Uses the same timeouts and user agent as [`ClientBuilder::new()`](crate::ClientBuilder::new)
+    fn default() -> Self {
+        crate::ClientBuilder::new().ureq_transport()
+    }
+}
+
+impl UreqTransport {
+    fn call(&self, url: &str) -> Result<ureq::Response, crate::Error> {
+        let request = self
+            .headers
+            .iter()
+            .fold(self.agent.get(url), |request, (name, value)| {
+                request.set(name, value)
+            });
+
+        match request.call() {
+            Ok(response) => Ok(response),
+            Err(ureq::Error::Status(_, response)) => Ok(response),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl Transport for UreqTransport {
+    fn fetch(&self, url: &str) -> Result<Response, crate::Error> {
+        let response = self.call(url)?;
+
+        let status = response.status();
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut body)
+            .map_err(crate::Error::body_read)?;
+
+        Ok(Response { status, body })
+    }
+
+    fn fetch_to(&self, url: &str, out: &mut dyn Write) -> Result<u16, crate::Error> {
+        let response = self.call(url)?;
+
+        let status = response.status();
+        // copied by hand since io::copy can't tell a broken response from a broken writer
+        if (200..300).contains(&status) {
+            let mut reader = response.into_reader();
+            let mut buf = [0; 8 * 1024];
+            loop {
+                let read = match reader.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(read) => read,
+                    Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(crate::Error::body_read(err)),
+                };
+                out.write_all(&buf[..read])?;
+            }
+        }
+
+        Ok(status)
+    }
+}
@@ -0,0 +1,555 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The format of a module, basically the extension of the module file. Formats the crate
+/// doesn't know about end up in [`ModuleFormat::Other`] with the text as the site shows it.
+///
+/// The [`Display`](fmt::Display) impl gives back the text the site uses, like `XM` or `IT`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "String", into = "String"))]
+pub enum ModuleFormat {
+    /// ProTracker and friends
+    Mod,
+    /// ScreamTracker 3
+    S3m,
+    /// FastTracker 2
+    Xm,
+    /// Impulse Tracker
+    It,
+    /// OpenMPT
+    Mptm,
+    /// ScreamTracker 2
+    Stm,
+    /// MultiTracker
+    Mtm,
+    /// OctaMED
+    Med,
+    /// Oktalyzer
+    Okt,
+    /// Composer 669
+    Composer669,
+    /// Farandole Composer
+    Far,
+    /// UltraTracker
+    Ult,
+    /// AHX
+    Ahx,
+    /// HivelyTracker
+    Hvl,
+    /// Anything else, holds the format text as it was on the page
+    Other(String),
+}
+
+impl ModuleFormat {
+    /// The format as Mod Archive writes it, `XM`, `IT` and so on
+    pub fn as_str(&self) -> &str {
+        match self {
+            ModuleFormat::Mod => "MOD",
+            ModuleFormat::S3m => "S3M",
+            ModuleFormat::Xm => "XM",
+            ModuleFormat::It => "IT",
+            ModuleFormat::Mptm => "MPTM",
+            ModuleFormat::Stm => "STM",
+            ModuleFormat::Mtm => "MTM",
+            ModuleFormat::Med => "MED",
+            ModuleFormat::Okt => "OKT",
+            ModuleFormat::Composer669 => "669",
+            ModuleFormat::Far => "FAR",
+            ModuleFormat::Ult => "ULT",
+            ModuleFormat::Ahx => "AHX",
+            ModuleFormat::Hvl => "HVL",
+            ModuleFormat::Other(format) => format,
+        }
+    }
+}
+
+impl FromStr for ModuleFormat {
+    type Err = std::convert::Infallible;
+
+    /// Case insensitive, anything unknown becomes [`ModuleFormat::Other`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "MOD" => ModuleFormat::Mod,
+            "S3M" => ModuleFormat::S3m,
+            "XM" => ModuleFormat::Xm,
+            "IT" => ModuleFormat::It,
+            "MPTM" => ModuleFormat::Mptm,
+            "STM" => ModuleFormat::Stm,
+            "MTM" => ModuleFormat::Mtm,
+            "MED" => ModuleFormat::Med,
+            "OKT" => ModuleFormat::Okt,
+            "669" => ModuleFormat::Composer669,
+            "FAR" => ModuleFormat::Far,
+            "ULT" => ModuleFormat::Ult,
+            "AHX" => ModuleFormat::Ahx,
+            "HVL" => ModuleFormat::Hvl,
+            _ => ModuleFormat::Other(s.into()),
+        })
+    }
+}
+
+impl From<String> for ModuleFormat {
+    fn from(s: String) -> Self {
+        s.parse().unwrap_or_else(|never| match never {})
+    }
+}
+
+impl From<ModuleFormat> for String {
+    fn from(format: ModuleFormat) -> Self {
+        match format {
+            ModuleFormat::Other(format) => format,
+            format => format.as_str().into(),
+        }
+    }
+}
+
+impl fmt::Display for ModuleFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A score out of 10 like the site's member and reviewer ratings, kept to two decimal places
+/// so it can be compared exactly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct Rating(u16);
+
+impl Rating {
+    /// A rating of `hundredths` hundredths of a point, `750` is a 7.5
+    pub fn from_hundredths(hundredths: u16) -> Self {
+        Rating(hundredths)
+    }
+
+    /// The rating in hundredths of a point
+    pub fn hundredths(&self) -> u16 {
+        self.0
+    }
+
+    /// The rating as a number from 0 to 10
+    pub fn as_f32(&self) -> f32 {
+        self.0 as f32 / 100.0
+    }
+}
+
+impl FromStr for Rating {
+    type Err = crate::Error;
+
+    /// Parses ratings like `7.5` or `8 / 10`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let score: f64 = s
+            .split('/')
+            .next()
+            .and_then(|score| score.trim().parse().ok())
+            .filter(|score| (0.0..=10.0).contains(score))
+            .ok_or_else(|| crate::Error::Parse {
+                field: "rating",
+                text: s.into(),
+            })?;
+
+        Ok(Rating((score * 100.0).round() as u16))
+    }
+}
+
+impl TryFrom<String> for Rating {
+    type Error = crate::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Rating> for String {
+    fn from(rating: Rating) -> Self {
+        rating.to_string()
+    }
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_f32())
+    }
+}
+
+/// The license a module was uploaded under, as shown on its page. The Creative Commons ones
+/// keep the link the page had since it says which version of the license applies, licenses
+/// the crate doesn't recognise end up in [`License::Other`] with the text and link as the site
+/// shows them.
+///
+/// The [`Display`](fmt::Display) impl gives the [description](License::description).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum License {
+    /// The Mod Archive Distribution license, the default for older uploads which only lets Mod
+    /// Archive distribute the module
+    ModArchiveDistribution,
+    /// Dedicated to the public domain, CC0 included
+    PublicDomain,
+    /// Creative Commons Attribution
+    CcBy { url: String },
+    /// Creative Commons Attribution Share Alike
+    CcBySa { url: String },
+    /// Creative Commons Attribution No Derivatives
+    CcByNd { url: String },
+    /// Creative Commons Attribution Non-commercial
+    CcByNc { url: String },
+    /// Creative Commons Attribution Non-commercial Share Alike
+    CcByNcSa { url: String },
+    /// Creative Commons Attribution Non-commercial No Derivatives
+    CcByNcNd { url: String },
+    /// The author kept every right to the module
+    AllRightsReserved,
+    /// Anything else, holds the license text and link as they were on the page
+    Other {
+        description: String,
+        url: Option<String>,
+    },
+}
+
+impl License {
+    /// Works out the license from the text on the page and the link next to it, the link wins
+    /// when both are there since the text varies more
+    pub(crate) fn from_page(text: &str, url: Option<&str>) -> Self {
+        let text = text.trim();
+
+        // creative commons links look like https://creativecommons.org/licenses/by-nc-sa/3.0/
+        let cc_kind = url.and_then(|url| {
+            let (_, path) = url.split_once("creativecommons.org/")?;
+            Some(
+                path.trim_matches('/')
+                    .split('/')
+                    .take(2)
+                    .collect::<Vec<_>>(),
+            )
+        });
+        let url = url.map(str::trim);
+        let cc_url = || url.unwrap_or_default().to_string();
+        let known = match cc_kind.as_deref() {
+            Some(["licenses", "by"]) => Some(License::CcBy { url: cc_url() }),
+            Some(["licenses", "by-sa"]) => Some(License::CcBySa { url: cc_url() }),
+            Some(["licenses", "by-nd"]) => Some(License::CcByNd { url: cc_url() }),
+            Some(["licenses", "by-nc"]) => Some(License::CcByNc { url: cc_url() }),
+            Some(["licenses", "by-nc-sa"]) => Some(License::CcByNcSa { url: cc_url() }),
+            Some(["licenses", "by-nc-nd"]) => Some(License::CcByNcNd { url: cc_url() }),
+            Some(["publicdomain", _]) => Some(License::PublicDomain),
+            _ => None,
+        };
+        if let Some(license) = known {
+            return license;
+        }
+
+        let lowercase = text.to_ascii_lowercase();
+        if lowercase.contains("mod archive distribution") {
+            License::ModArchiveDistribution
+        } else if lowercase.contains("public domain") {
+            License::PublicDomain
+        } else if lowercase.contains("all rights reserved") {
+            License::AllRightsReserved
+        } else {
+            License::Other {
+                description: text.into(),
+                url: url.map(Into::into),
+            }
+        }
+    }
+
+    /// A short description of the license, the name the site gives it for the known ones
+    pub fn description(&self) -> &str {
+        match self {
+            License::ModArchiveDistribution => "Mod Archive Distribution license",
+            License::PublicDomain => "Public Domain",
+            License::CcBy { .. } => "Attribution",
+            License::CcBySa { .. } => "Attribution Share Alike",
+            License::CcByNd { .. } => "Attribution No Derivatives",
+            License::CcByNc { .. } => "Attribution Non-commercial",
+            License::CcByNcSa { .. } => "Attribution Non-commercial Share Alike",
+            License::CcByNcNd { .. } => "Attribution Non-commercial No Derivatives",
+            License::AllRightsReserved => "All Rights Reserved",
+            License::Other { description, .. } => description,
+        }
+    }
+
+    /// Where the full license text lives, the Creative Commons ones give the link from the
+    /// page so it's the version the module was actually licensed under. Public domain and all
+    /// rights reserved don't have one.
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            License::ModArchiveDistribution => {
+                Some("https://modarchive.org/index.php?terms-upload")
+            }
+            License::PublicDomain | License::AllRightsReserved => None,
+            License::CcBy { url }
+            | License::CcBySa { url }
+            | License::CcByNd { url }
+            | License::CcByNc { url }
+            | License::CcByNcSa { url }
+            | License::CcByNcNd { url } => Some(url),
+            License::Other { url, .. } => url.as_deref(),
+        }
+    }
+
+    /// Whether the module may be used in something you sell, licenses the crate doesn't know
+    /// are assumed not to allow it
+    pub fn allows_commercial_use(&self) -> bool {
+        matches!(
+            self,
+            License::PublicDomain
+                | License::CcBy { .. }
+                | License::CcBySa { .. }
+                | License::CcByNd { .. }
+        )
+    }
+
+    /// Whether the module may be remixed, edited or otherwise built on, licenses the crate
+    /// doesn't know are assumed not to allow it
+    pub fn allows_derivatives(&self) -> bool {
+        matches!(
+            self,
+            License::PublicDomain
+                | License::CcBy { .. }
+                | License::CcBySa { .. }
+                | License::CcByNc { .. }
+                | License::CcByNcSa { .. }
+        )
+    }
+}
+
+impl fmt::Display for License {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// A Mod Archive member as linked from a page, an artist credited on a module for example
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Member {
+    /// The member ID, use [`Artist::get()`](crate::Artist::get) to get their profile
+    pub id: u32,
+    /// The name the member goes by
+    pub name: String,
+}
+
+/// A 16 byte MD5 digest, parsed from (and displayed as) 32 hex characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct Md5Digest(pub [u8; 16]);
+
+impl Md5Digest {
+    /// The raw bytes of the digest
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    /// Hashes `data`
+    pub fn compute(data: impl AsRef<[u8]>) -> Self {
+        Md5Digest(md5::compute(data).0)
+    }
+
+    /// Hashes everything `reader` has left in it
+    pub fn from_reader(mut reader: impl std::io::Read) -> std::io::Result<Self> {
+        let mut writer = HashingWriter::new(std::io::sink());
+        std::io::copy(&mut reader, &mut writer)?;
+        Ok(writer.digest())
+    }
+}
+
+/// Passes everything written to it on to `inner` and hashes it along the way
+pub(crate) struct HashingWriter<W> {
+    inner: W,
+    context: md5::Context,
+    written: u64,
+}
+
+impl<W: std::io::Write> HashingWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        HashingWriter {
+            inner,
+            context: md5::Context::new(),
+            written: 0,
+        }
+    }
+
+    /// How many bytes went through so far
+    pub(crate) fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// The digest of everything that went through
+    pub(crate) fn digest(self) -> Md5Digest {
+        Md5Digest(self.context.finalize().0)
+    }
+}
+
+impl<W: std::io::Write> std::io::Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.context.consume(&buf[..written]);
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl FromStr for Md5Digest {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || crate::Error::Parse {
+            field: "md5",
+            text: s.into(),
+        };
+
+        let hex = s.trim();
+        // from_str_radix takes a leading `+` so the digits are checked up front
+        if hex.len() != 32 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+
+        let mut digest = [0; 16];
+        for (byte, pair) in digest.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+        }
+
+        Ok(Md5Digest(digest))
+    }
+}
+
+impl TryFrom<String> for Md5Digest {
+    type Error = crate::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Md5Digest> for String {
+    fn from(digest: Md5Digest) -> Self {
+        digest.to_string()
+    }
+}
+
+impl fmt::Display for Md5Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, License, Md5Digest, ModuleFormat, Rating};
+
+    #[test]
+    fn format_round_trip() {
+        assert_eq!("xm".parse::<ModuleFormat>().unwrap(), ModuleFormat::Xm);
+        assert_eq!(ModuleFormat::Composer669.to_string(), "669");
+
+        let other: ModuleFormat = "SID".parse().unwrap();
+        assert_eq!(other, ModuleFormat::Other("SID".into()));
+        assert_eq!(other.to_string(), "SID");
+    }
+
+    #[test]
+    fn ratings() {
+        assert_eq!("7.5".parse::<Rating>().unwrap().hundredths(), 750);
+        assert_eq!(
+            "8 / 10".parse::<Rating>().unwrap(),
+            Rating::from_hundredths(800)
+        );
+        assert_eq!(Rating::from_hundredths(746).to_string(), "7.46");
+        assert!("11".parse::<Rating>().is_err());
+        assert!("Unrated".parse::<Rating>().is_err());
+    }
+
+    #[test]
+    fn md5_compute() {
+        let digest = Md5Digest::compute("trackermeta");
+        assert_eq!(
+            Md5Digest::from_reader("trackermeta".as_bytes()).unwrap(),
+            digest
+        );
+        assert_eq!(
+            Md5Digest::compute("").to_string(),
+            "d41d8cd98f00b204e9800998ecf8427e"
+        );
+    }
+
+    #[test]
+    fn md5_validation() {
+        let digest: Md5Digest = "9a0364b9e99bb480dd25e1f0284c8555".parse().unwrap();
+        assert_eq!(digest.as_bytes()[0], 0x9a);
+        assert_eq!(digest.to_string(), "9a0364b9e99bb480dd25e1f0284c8555");
+
+        for invalid in [
+            "",
+            "9a0364b9",
+            "zz0364b9e99bb480dd25e1f0284c8555",
+            "+a0364b9e99bb480dd25e1f0284c8555",
+        ] {
+            assert_eq!(
+                invalid.parse::<Md5Digest>().unwrap_err(),
+                Error::Parse {
+                    field: "md5",
+                    text: invalid.into()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn licenses() {
+        let license = License::from_page(
+            "Attribution Non-commercial Share Alike",
+            Some("https://creativecommons.org/licenses/by-nc-sa/3.0/"),
+        );
+        assert_eq!(
+            license,
+            License::CcByNcSa {
+                url: "https://creativecommons.org/licenses/by-nc-sa/3.0/".into()
+            }
+        );
+        assert!(!license.allows_commercial_use());
+        assert!(license.allows_derivatives());
+
+        // older links use http and other versions, the link is kept as it was
+        let license =
+            License::from_page("", Some("http://creativecommons.org/licenses/by-nd/2.5/"));
+        assert!(matches!(license, License::CcByNd { .. }));
+        assert_eq!(
+            license.url(),
+            Some("http://creativecommons.org/licenses/by-nd/2.5/")
+        );
+        assert_eq!(
+            License::from_page(
+                "CC0",
+                Some("https://creativecommons.org/publicdomain/zero/1.0/")
+            ),
+            License::PublicDomain
+        );
+        assert_eq!(
+            License::from_page("Mod Archive Distribution license", None),
+            License::ModArchiveDistribution
+        );
+        assert_eq!(License::from_page("All rights reserved", None).url(), None);
+
+        let other = License::from_page("WTFPL", Some("http://www.wtfpl.net/"));
+        assert_eq!(other.to_string(), "WTFPL");
+        assert_eq!(other.url(), Some("http://www.wtfpl.net/"));
+        assert!(!other.allows_commercial_use());
+        assert!(!other.allows_derivatives());
+
+        assert!(License::PublicDomain.allows_commercial_use());
+        assert!(!License::ModArchiveDistribution.allows_derivatives());
+    }
+}